@@ -1,6 +1,23 @@
-use clap::{Arg, ArgAction, Command};
-use lpm_core::ProcessManager;
-use sysinfo::ProcessExt;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::collections::HashSet;
+use std::thread;
+use std::time::{Duration, Instant};
+use lpm_core::{
+    format_cpu_list, parse_cpu_list, parse_time, resolve_group, ActionKind, AlertRule, AlertStatus, CgroupLimit, Column,
+    Connection, ExportFormat, FdKind, HistoryQuery, IoClass, MemoryUsage, PidResult, ProcessManager, ProcessSnapshot, ProcessState,
+    Query, Resource, Retention, RestartOptions, SchedPolicy, Signal, TermStage, TreeOrder, DEFAULT_GRACE,
+};
+
+fn print_process(p: &ProcessSnapshot) {
+    let marker = match p.state {
+        ProcessState::Stopped | ProcessState::Tracing => " (stopped)",
+        _ => "",
+    };
+    println!(
+        "[{}] {} CPU: {:.2}% MEM: {} KB SCHED: {} IO: {} OOM: {} ({:+}){}",
+        p.pid, p.name, p.cpu_usage, p.memory / 1024, p.scheduling, p.io_priority, p.oom_score, p.oom_score_adj, marker
+    );
+}
 
 fn main() {
     let matches = Command::new("LPM CLI")
@@ -18,38 +35,73 @@ fn main() {
             Arg::new("user")
                 .short('u')
                 .long("user")
-                .help("Filter by user")
+                .help("Filter by user name or uid (real or effective)")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("group")
+                .short('g')
+                .long("group")
+                .help("Filter by group name or gid (real, effective or supplementary)")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("where")
+                .short('w')
+                .long("where")
+                .help("Filter with a query, e.g. 'cpu > 50 and user == \"postgres\" and cmd ~ /--worker/'")
                 .action(ArgAction::Set),
         )
         .arg(
             Arg::new("sort")
                 .short('s')
                 .long("sort")
-                .help("Sort by: cpu or mem")
+                .help("Sort by: cpu, mem, oom, pss or uss (pss and uss read smaps, which needs access to the process)")
                 .action(ArgAction::Set),
         )
         .arg(
             Arg::new("kill")
                 .long("kill")
-                .help("Kill a process by PID")
+                .help("Terminate a process by PID (SIGTERM, then SIGKILL after a grace period)")
                 .action(ArgAction::Set),
         )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("With --kill, send SIGKILL right away")
+                .requires("kill")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("restart")
                 .long("restart")
-                .help("Restart a process by PID")
+                .help("Restart a process by PID with its original argv, cwd and environment")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("detach")
+                .long("detach")
+                .help("With --restart, start the new process in its own session")
+                .requires("restart")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("log")
+                .long("log")
+                .help("With --restart, append the new process's stdout/stderr to this file")
+                .requires("restart")
                 .action(ArgAction::Set),
         )
         .arg(
             Arg::new("export")
                 .long("export")
-                .help("Export process list to a JSON file (pass filename)")
+                .help("Export process list to a file (format from extension, JSON otherwise)")
                 .action(ArgAction::Set),
         )
         .arg(
             Arg::new("alerts")
                 .long("alerts")
-                .help("Check high CPU/RAM alerts")
+                .help("Check for sustained high CPU/RAM usage with the default alert rules")
                 .action(ArgAction::SetTrue),
         )
         .arg(
@@ -58,68 +110,1230 @@ fn main() {
                 .help("Show process action history")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .help("Only show history from this time on (YYYY-MM-DD [HH:MM[:SS]] or RFC 3339)")
+                .requires("history")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("until")
+                .long("until")
+                .help("Only show history up to this time")
+                .requires("history")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("action")
+                .long("action")
+                .help("Only show history for this action (e.g. kill, terminate, signal, renice)")
+                .requires("history")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("pid")
+                .long("pid")
+                .help("Only show history for this PID")
+                .requires("history")
+                .action(ArgAction::Set),
+        )
+        .subcommand(
+            Command::new("signal")
+                .about("Send a signal to one or more processes")
+                .arg(
+                    Arg::new("signal")
+                        .help("Signal name or number (TERM, SIGHUP, 10, RTMIN+2, ...)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("pids")
+                        .help("Target PIDs")
+                        .required(true)
+                        .num_args(1..),
+                ),
+        )
+        .subcommand(
+            Command::new("kill")
+                .about("Terminate a process, or signal a whole process subtree with --tree")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("tree")
+                        .long("tree")
+                        .help("Also signal every descendant of the process")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Send SIGKILL right away")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("signal")
+                        .long("signal")
+                        .help("Signal to send to the subtree (default TERM, or KILL with --force)")
+                        .requires("tree")
+                        .conflicts_with("force")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("order")
+                        .long("order")
+                        .help("Subtree order: leaves (children first) or root (parents first)")
+                        .requires("tree")
+                        .default_value("leaves")
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("suspend")
+                .about("Pause a process with SIGSTOP")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("tree")
+                        .long("tree")
+                        .help("Also pause every descendant")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("resume")
+                .about("Continue a paused process with SIGCONT")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("tree")
+                        .long("tree")
+                        .help("Also continue every descendant")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export the process list as JSON, CSV, NDJSON or Markdown")
+                .arg(Arg::new("file").help("Output file").required(true))
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("json, csv, ndjson or markdown (default: from the file extension)")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("columns")
+                        .long("columns")
+                        .help("Comma-separated columns, e.g. pid,name,cpu,memory (default: all)")
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("alerts")
+                .about("Evaluate alert rules over a few refreshes, or keep watching with --watch")
+                .arg(
+                    Arg::new("rules")
+                        .long("rules")
+                        .help("JSON file with an array of alert rules (default: high CPU and memory)")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .help("Seconds between refreshes")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .help("Keep running and print alerts as they fire and resolve")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("trend")
+                .about("Sample one process for a while and show how its usage changes")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("duration")
+                        .long("duration")
+                        .help("Seconds to sample for")
+                        .default_value("10")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .help("Seconds between samples")
+                        .default_value("1")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("info")
+                .about("Show detailed information about a process")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("env")
+                        .long("env")
+                        .help("Also print the environment")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print everything, including the environment, as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("threads")
+                .about("List the threads of a process, or change one thread's nice value or CPU affinity")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("tid")
+                        .long("tid")
+                        .help("Thread to change with --nice or --affinity")
+                        .value_parser(clap::value_parser!(u32))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("nice")
+                        .long("nice")
+                        .help("New nice value for the thread")
+                        .requires("tid")
+                        .allow_hyphen_values(true)
+                        .value_parser(clap::value_parser!(i32))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("affinity")
+                        .long("affinity")
+                        .help("CPUs the thread may run on, as a list like 0-3,8")
+                        .requires("tid")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the threads as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("files")
+                .about("List the open files of a process, or find the processes holding a path open")
+                .arg(Arg::new("pid").help("Target PID").required_unless_present("path"))
+                .arg(
+                    Arg::new("path")
+                        .long("path")
+                        .help("List the processes that have this file open instead")
+                        .conflicts_with("pid")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("kind")
+                        .long("kind")
+                        .help("Only show descriptors of this kind")
+                        .value_parser(FdKind::ALL.iter().map(|k| k.as_str()).collect::<Vec<_>>())
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the result as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("ports")
+                .about("List listening sockets and the processes that own them")
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .help("Only show what listens on this TCP or UDP port")
+                        .value_parser(clap::value_parser!(u16))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("pid")
+                        .long("pid")
+                        .help("Show every socket of this process instead")
+                        .conflicts_with("port")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .help("Include connected and Unix sockets")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the sockets as JSON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("memory")
+                .about("Show RSS, PSS, USS and swap of a process from smaps")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("mappings")
+                        .long("mappings")
+                        .help("Also list this many mappings with the largest PSS")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("affinity")
+                .about("Show or set the CPUs a process may run on")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(Arg::new("cpus").help("New CPU list for all threads, e.g. 0-3,8")),
+        )
+        .subcommand(
+            Command::new("sched")
+                .about("Show or set the scheduling policy and real-time priority of a process")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("policy")
+                        .help("New policy for all threads")
+                        .value_parser(["other", "fifo", "rr", "batch", "idle"]),
+                )
+                .arg(
+                    Arg::new("priority")
+                        .long("priority")
+                        .short('p')
+                        .help("Real-time priority 1-99, for fifo and rr")
+                        .requires("policy")
+                        .value_parser(clap::value_parser!(u32))
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("ionice")
+                .about("Show or set the I/O scheduling class and level of a process or process group")
+                .arg(Arg::new("pid").help("Target PID, or process group ID with --pgrp").required(true))
+                .arg(
+                    Arg::new("class")
+                        .help("New I/O class for all threads")
+                        .value_parser(["none", "realtime", "best-effort", "idle"]),
+                )
+                .arg(
+                    Arg::new("level")
+                        .long("level")
+                        .short('n')
+                        .help("Level 0 (highest) to 7, for realtime and best-effort")
+                        .requires("class")
+                        .value_parser(clap::value_parser!(u8))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("pgrp")
+                        .long("pgrp")
+                        .short('P')
+                        .help("Apply to every process in the process group")
+                        .requires("class")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("limits")
+                .about("Show or change the resource limits of a process")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("set")
+                        .long("set")
+                        .value_name("RESOURCE=SOFT[:HARD]")
+                        .help("Change a limit, e.g. NOFILE=65536, NOFILE=1024:4096 or CORE=unlimited:; repeatable")
+                        .action(ArgAction::Append),
+                ),
+        )
+        .subcommand(
+            Command::new("cgroup")
+                .about("Inspect and control cgroup v2 groups")
+                .subcommand_required(true)
+                .arg(
+                    Arg::new("root")
+                        .long("root")
+                        .global(true)
+                        .help("cgroup v2 mount point (default: detected from /proc/self/mountinfo)")
+                        .action(ArgAction::Set),
+                )
+                .subcommand(
+                    Command::new("show")
+                        .about("Show usage and limits of a process's cgroup, or of a cgroup path")
+                        .arg(Arg::new("target").help("PID, or a cgroup path such as /lpm/hogs").required(true)),
+                )
+                .subcommand(
+                    Command::new("create")
+                        .about("Create a cgroup and enable the parent's controllers for it")
+                        .arg(Arg::new("path").help("cgroup path such as /lpm/hogs").required(true)),
+                )
+                .subcommand(
+                    Command::new("move")
+                        .about("Move a process into a cgroup")
+                        .arg(Arg::new("pid").help("Target PID").required(true))
+                        .arg(Arg::new("path").help("Destination cgroup path").required(true)),
+                )
+                .subcommand(
+                    Command::new("limit")
+                        .about("Set a limit of a cgroup")
+                        .arg(Arg::new("path").help("cgroup path").required(true))
+                        .arg(
+                            Arg::new("resource")
+                                .help("Limit to set")
+                                .required(true)
+                                .value_parser(["cpu", "memory", "pids", "io"]),
+                        )
+                        .arg(
+                            Arg::new("value")
+                                .help("cpu: 50% or 'QUOTA PERIOD'; memory: 512M; pids: 100; io: 'MAJ:MIN wbps=N'; or max")
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("oom")
+                .about("Show or set the OOM score adjustment of a process")
+                .arg(Arg::new("pid").help("Target PID").required(true))
+                .arg(
+                    Arg::new("adj")
+                        .help("New oom_score_adj: -1000 (never killed) to 1000 (killed first)")
+                        .allow_negative_numbers(true)
+                        .value_parser(clap::value_parser!(i32)),
+                ),
+        )
         .get_matches();
 
     let mut manager = ProcessManager::new();
 
-    if let Some(name) = matches.get_one::<String>("filter") {
-        for p in manager.list_processes_by_name(name) {
-            println!("[{}] {} CPU: {:.2}% MEM: {} KB", p.pid(), p.name(), p.cpu_usage(), p.memory());
-        }
-    } else if let Some(user) = matches.get_one::<String>("user") {
-        for p in manager.list_processes_by_user(user) {
-            println!("[{}] {} CPU: {:.2}% MEM: {} KB", p.pid(), p.name(), p.cpu_usage(), p.memory());
-        }
-    } else if let Some(sort_by) = matches.get_one::<String>("sort") {
-        let mut processes = manager.list_processes();
-        if sort_by == "cpu" {
-            processes.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap_or(std::cmp::Ordering::Equal));
-        } else if sort_by == "mem" {
-            processes.sort_by(|a, b| b.memory().cmp(&a.memory()));
-        }
-        for p in processes {
-            println!("[{}] {} CPU: {:.2}% MEM: {} KB", p.pid(), p.name(), p.cpu_usage(), p.memory());
-        }
+    if let Some(("signal", sub)) = matches.subcommand() {
+        run_signal(&mut manager, sub);
+    } else if let Some(("kill", sub)) = matches.subcommand() {
+        run_kill(&mut manager, sub);
+    } else if let Some(("suspend", sub)) = matches.subcommand() {
+        run_suspend(&mut manager, sub, true);
+    } else if let Some(("resume", sub)) = matches.subcommand() {
+        run_suspend(&mut manager, sub, false);
+    } else if let Some(("export", sub)) = matches.subcommand() {
+        run_export(&mut manager, sub);
+    } else if let Some(("info", sub)) = matches.subcommand() {
+        run_info(&mut manager, sub);
+    } else if let Some(("affinity", sub)) = matches.subcommand() {
+        run_affinity(&mut manager, sub);
+    } else if let Some(("sched", sub)) = matches.subcommand() {
+        run_sched(&mut manager, sub);
+    } else if let Some(("ionice", sub)) = matches.subcommand() {
+        run_ionice(&mut manager, sub);
+    } else if let Some(("limits", sub)) = matches.subcommand() {
+        run_limits(&mut manager, sub);
+    } else if let Some(("cgroup", sub)) = matches.subcommand() {
+        run_cgroup(&mut manager, sub);
+    } else if let Some(("oom", sub)) = matches.subcommand() {
+        run_oom(&mut manager, sub);
+    } else if let Some(("files", sub)) = matches.subcommand() {
+        run_files(&mut manager, sub);
+    } else if let Some(("memory", sub)) = matches.subcommand() {
+        run_memory(&mut manager, sub);
+    } else if let Some(("ports", sub)) = matches.subcommand() {
+        run_ports(&mut manager, sub);
+    } else if let Some(("threads", sub)) = matches.subcommand() {
+        run_threads(&mut manager, sub);
+    } else if let Some(("trend", sub)) = matches.subcommand() {
+        run_trend(&mut manager, sub);
+    } else if let Some(("alerts", sub)) = matches.subcommand() {
+        let interval = *sub.get_one::<f64>("interval").expect("defaulted");
+        run_alerts(
+            &mut manager,
+            sub.get_one::<String>("rules").map(String::as_str),
+            Duration::from_secs_f64(interval.max(0.1)),
+            sub.get_flag("watch"),
+        );
+    } else if ["filter", "user", "group", "where", "sort"].iter().any(|id| matches.get_one::<String>(id).is_some()) {
+        run_list(&mut manager, &matches);
     } else if let Some(pid_str) = matches.get_one::<String>("kill") {
         if let Ok(pid) = pid_str.parse::<usize>() {
-            if manager.kill_process(pid) {
-                println!("Killed process {}", pid);
-            } else {
-                println!("Failed to kill process {}", pid);
-            }
+            kill_one(&mut manager, pid, matches.get_flag("force"));
         } else {
             println!("Invalid PID: {}", pid_str);
         }
     } else if let Some(pid_str) = matches.get_one::<String>("restart") {
         if let Ok(pid) = pid_str.parse::<usize>() {
-            if manager.restart_process(pid) {
-                println!("Restarted process {}", pid);
-            } else {
-                println!("Failed to restart process {}", pid);
+            let options = RestartOptions {
+                detach: matches.get_flag("detach"),
+                log_file: matches.get_one::<String>("log").map(Into::into),
+                ..RestartOptions::default()
+            };
+            match manager.restart_process(pid, &options) {
+                Ok(new_pid) => println!("Restarted process {} as {}", pid, new_pid),
+                Err(e) => println!("Failed to restart process {}: {}", pid, e),
             }
         } else {
             println!("Invalid PID: {}", pid_str);
         }
     } else if let Some(file) = matches.get_one::<String>("export") {
-        if let Err(e) = manager.export_processes("json", file) {
-            println!("Export failed: {}", e);
-        } else {
-            println!("Exported to {}", file);
+        let format = ExportFormat::from_path(file.as_ref()).unwrap_or(ExportFormat::Json);
+        match manager.export_processes(file, Some(format), None) {
+            Ok(count) => println!("Exported {} processes to {}", count, file),
+            Err(e) => println!("Export failed: {}", e),
         }
     } else if matches.get_flag("alerts") {
-        let alerts = manager.check_alerts(80.0, 500_000);
-        if alerts.is_empty() {
-            println!("No high CPU/RAM processes.");
+        run_alerts(&mut manager, None, Duration::from_secs(1), false);
+    } else if matches.get_flag("history") {
+        match history_query(&matches) {
+            Ok(query) => println!("History:\n{}", manager.show_history(&query)),
+            Err(e) => println!("{}", e),
+        }
+    } else {
+        println!("No command provided. Run with --help to see options.");
+    }
+}
+
+
+/// Lists processes matching every given filter (--filter, --user, --group, --where),
+/// optionally sorted.
+fn run_list(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let query = match matches.get_one::<String>("where").map(|q| (q, Query::parse(q))) {
+        Some((_, Ok(query))) => Some(query),
+        Some((source, Err(e))) => {
+            println!("Invalid query: {}\n{}", e, e.pointer(source));
+            return;
+        }
+        None => None,
+    };
+    let name = matches.get_one::<String>("filter").map(|n| n.to_lowercase());
+    let user = matches.get_one::<String>("user");
+    let group = match matches.get_one::<String>("group").map(|g| (g, resolve_group(g))) {
+        Some((_, Some(gid))) => Some(gid),
+        Some((name, None)) => {
+            println!("Unknown group: {}", name);
+            return;
+        }
+        None => None,
+    };
+
+    // CPU usage is measured between two refreshes.
+    manager.list_processes();
+    thread::sleep(Duration::from_millis(250));
+    let mut processes: Vec<_> = manager
+        .list_processes()
+        .into_iter()
+        .filter(|p| name.as_ref().is_none_or(|n| p.name.to_lowercase().contains(n)))
+        .filter(|p| user.is_none_or(|u| p.matches_user(u)))
+        .filter(|p| group.is_none_or(|gid| p.in_group(gid)))
+        .filter(|p| query.as_ref().is_none_or(|q| q.matches(p)))
+        .collect();
+    match matches.get_one::<String>("sort").map(String::as_str) {
+        Some("cpu") => processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(std::cmp::Ordering::Equal)),
+        Some("mem") => processes.sort_by_key(|p| std::cmp::Reverse(p.memory)),
+        Some("oom") => processes.sort_by_key(|p| std::cmp::Reverse(p.oom_score)),
+        Some(key @ ("pss" | "uss")) => {
+            // Processes whose smaps cannot be read sort last.
+            let mut usage: Vec<_> = processes
+                .into_iter()
+                .map(|p| {
+                    let usage = manager.memory_usage(p.pid as usize).ok();
+                    (p, usage)
+                })
+                .collect();
+            let value = |u: &Option<MemoryUsage>| u.map(|u| if key == "pss" { u.pss } else { u.uss });
+            usage.sort_by_key(|(_, u)| std::cmp::Reverse(value(u)));
+            for (p, u) in usage {
+                match u {
+                    Some(u) => println!(
+                        "[{}] {} CPU: {:.2}% RSS: {} KB PSS: {} KB USS: {} KB SWAP: {} KB",
+                        p.pid, p.name, p.cpu_usage, u.rss / 1024, u.pss / 1024, u.uss / 1024, u.swap / 1024
+                    ),
+                    None => println!("[{}] {} CPU: {:.2}% RSS: {} KB PSS: ?", p.pid, p.name, p.cpu_usage, p.memory / 1024),
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+    for p in processes {
+        print_process(&p);
+    }
+}
+
+fn history_query(matches: &ArgMatches) -> Result<HistoryQuery, String> {
+    let time = |id: &str| -> Result<_, String> {
+        matches
+            .get_one::<String>(id)
+            .map(|s| parse_time(s).ok_or_else(|| format!("Invalid time: {}", s)))
+            .transpose()
+    };
+    Ok(HistoryQuery {
+        since: time("since")?,
+        until: time("until")?,
+        action: matches
+            .get_one::<String>("action")
+            .map(|s| s.parse::<ActionKind>())
+            .transpose()?,
+        pid: matches
+            .get_one::<String>("pid")
+            .map(|s| s.parse::<u32>().map_err(|_| format!("Invalid PID: {}", s)))
+            .transpose()?,
+    })
+}
+
+fn run_signal(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let sig_str = matches.get_one::<String>("signal").expect("required");
+    let signal = match sig_str.parse::<Signal>() {
+        Ok(signal) => signal,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    for pid_str in matches.get_many::<String>("pids").into_iter().flatten() {
+        match pid_str.parse::<usize>() {
+            Ok(pid) => match manager.send_signal(pid, signal) {
+                Ok(()) => println!("Sent {} to process {}", signal, pid),
+                Err(e) => println!("Failed to send {} to process {}: {}", signal, pid, e),
+            },
+            Err(_) => println!("Invalid PID: {}", pid_str),
+        }
+    }
+}
+
+fn kill_one(manager: &mut ProcessManager, pid: usize, force: bool) {
+    if force {
+        match manager.kill_process(pid) {
+            Ok(()) => println!("Killed process {}", pid),
+            Err(e) => println!("Failed to kill process {}: {}", pid, e),
+        }
+    } else {
+        match manager.terminate(pid, DEFAULT_GRACE) {
+            Ok(TermStage::Terminated) => println!("Terminated process {} with SIGTERM", pid),
+            Ok(TermStage::Killed) => println!(
+                "Killed process {} with SIGKILL after {}s grace period",
+                pid,
+                DEFAULT_GRACE.as_secs()
+            ),
+            Err(e) => println!("Failed to terminate process {}: {}", pid, e),
+        }
+    }
+}
+
+fn run_kill(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    if !matches.get_flag("tree") {
+        kill_one(manager, pid, matches.get_flag("force"));
+        return;
+    }
+
+    let signal = match matches.get_one::<String>("signal") {
+        Some(s) => match s.parse::<Signal>() {
+            Ok(signal) => signal,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        },
+        None if matches.get_flag("force") => Signal::KILL,
+        None => Signal::TERM,
+    };
+    let order = match matches.get_one::<String>("order").expect("defaulted").parse::<TreeOrder>() {
+        Ok(order) => order,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    match manager.signal_tree(pid, signal, order) {
+        Ok(results) => {
+            for (pid, result) in results {
+                match result {
+                    Ok(()) => println!("Sent {} to process {}", signal, pid),
+                    Err(e) => println!("Failed to send {} to process {}: {}", signal, pid, e),
+                }
+            }
+        }
+        Err(e) => println!("Failed to signal tree of process {}: {}", pid, e),
+    }
+}
+
+fn run_suspend(manager: &mut ProcessManager, matches: &ArgMatches, suspend: bool) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let (verb, done) = if suspend { ("suspend", "Suspended") } else { ("resume", "Resumed") };
+
+    let results: Vec<PidResult> = if matches.get_flag("tree") {
+        let tree = if suspend { manager.suspend_tree(pid) } else { manager.resume_tree(pid) };
+        match tree {
+            Ok(results) => results,
+            Err(e) => {
+                println!("Failed to {} tree of process {}: {}", verb, pid, e);
+                return;
+            }
+        }
+    } else {
+        let result = if suspend { manager.suspend(pid) } else { manager.resume(pid) };
+        vec![(pid, result)]
+    };
+    for (pid, result) in results {
+        match result {
+            Ok(()) => println!("{} process {}", done, pid),
+            Err(e) => println!("Failed to {} process {}: {}", verb, pid, e),
+        }
+    }
+}
+
+fn run_export(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let file = matches.get_one::<String>("file").expect("required");
+    let format = matches.get_one::<String>("format").map(|f| f.parse::<ExportFormat>()).transpose();
+    let columns = matches.get_one::<String>("columns").map(|c| Column::parse_list(c)).transpose();
+    let result = format.and_then(|format| {
+        columns.and_then(|columns| manager.export_processes(file, format, columns.as_deref()))
+    });
+    match result {
+        Ok(count) => println!("Exported {} processes to {}", count, file),
+        Err(e) => println!("Export failed: {}", e),
+    }
+}
+
+fn run_alerts(manager: &mut ProcessManager, rules_file: Option<&str>, interval: Duration, watch: bool) {
+    if let Some(file) = rules_file {
+        match AlertRule::load(file) {
+            Ok(rules) => manager.set_alert_rules(rules),
+            Err(e) => {
+                println!("Failed to load alert rules: {}", e);
+                return;
+            }
+        }
+    }
+
+    if watch {
+        // Only print transitions and automatic actions, not every refresh of
+        // a firing alert.
+        let mut reported = HashSet::new();
+        loop {
+            for alert in manager.check_alerts() {
+                let key = (alert.rule.clone(), alert.pid);
+                let changed = match alert.status {
+                    AlertStatus::Firing => reported.insert(key),
+                    AlertStatus::Resolved => reported.remove(&key),
+                };
+                if changed || !alert.actions.is_empty() {
+                    println!("{} {}", alert.last_seen.format("%Y-%m-%d %H:%M:%S"), alert);
+                }
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    // A sustained condition can only fire once it has been sampled for the
+    // rule's whole duration; the first refresh only primes CPU usage.
+    let deadline = Instant::now() + interval + manager.alert_engine().longest_duration();
+    manager.check_alerts();
+    let alerts = loop {
+        thread::sleep(interval);
+        let alerts = manager.check_alerts();
+        if Instant::now() >= deadline {
+            break alerts;
+        }
+    };
+
+    let firing: Vec<_> = alerts.iter().filter(|a| a.status == AlertStatus::Firing).collect();
+    if firing.is_empty() {
+        println!("No alerts.");
+    } else {
+        println!("Alerts:");
+        for alert in firing {
+            println!("{}", alert);
+        }
+    }
+}
+
+fn run_trend(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let duration = Duration::from_secs_f64(matches.get_one::<f64>("duration").expect("defaulted").max(0.0));
+    let interval = Duration::from_secs_f64(matches.get_one::<f64>("interval").expect("defaulted").max(0.1));
+    let retention = manager.metric_retention();
+    manager.set_metric_retention(Retention { max_age: retention.max_age.max(duration), ..retention });
+
+    // The first refresh only primes CPU usage.
+    manager.sample_metrics();
+    let start = Instant::now();
+    println!("{:>8}  {:>7}  {:>10}  {:>10}  {:>10}  {:>10}  {:>7}", "TIME", "CPU%", "RSS KB", "VIRT KB", "READ B", "WRITE B", "THREADS");
+    // The store drops a process's samples once it exits, so keep the ones shown.
+    let mut samples = Vec::new();
+    while start.elapsed() < duration {
+        thread::sleep(interval);
+        manager.sample_metrics();
+        let Some(s) = manager.history_for(pid, duration).pop() else {
+            println!("Process {} is not running", pid);
+            break;
+        };
+        println!(
+            "{:>8}  {:>7.2}  {:>10}  {:>10}  {:>10}  {:>10}  {:>7}",
+            s.timestamp.format("%H:%M:%S"),
+            s.cpu_usage,
+            s.memory / 1024,
+            s.virtual_memory / 1024,
+            s.read_bytes,
+            s.written_bytes,
+            s.threads
+        );
+        samples.push(s);
+    }
+
+    if samples.is_empty() {
+        return;
+    }
+    let n = samples.len() as f64;
+    let cpu: Vec<f64> = samples.iter().map(|s| s.cpu_usage as f64).collect();
+    let rss: Vec<f64> = samples.iter().map(|s| (s.memory / 1024) as f64).collect();
+    let summary = |values: &[f64]| {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        format!("min {:.2}  avg {:.2}  max {:.2}", min, values.iter().sum::<f64>() / n, max)
+    };
+    println!("CPU%:   {}", summary(&cpu));
+    println!("RSS KB: {}", summary(&rss));
+}
+
+fn run_info(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let info = match manager.inspect(pid) {
+        Ok(info) => info,
+        Err(e) => {
+            println!("Failed to inspect process {}: {}", pid, e);
+            return;
+        }
+    };
+    if matches.get_flag("json") {
+        match serde_json::to_string_pretty(&info) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Failed to encode process {}: {}", pid, e),
+        }
+        return;
+    }
+    println!("{}", info);
+    if matches.get_flag("env") {
+        match &info.environment {
+            Some(vars) => {
+                println!("Environment:");
+                for (key, value) in vars {
+                    println!("  {}={}", key, value);
+                }
+            }
+            None => println!("Environment:   (not readable)"),
+        }
+    }
+}
+
+fn run_threads(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+
+    if let Some(&tid) = matches.get_one::<u32>("tid") {
+        let nice = matches.get_one::<i32>("nice");
+        let affinity = matches.get_one::<String>("affinity");
+        if nice.is_none() && affinity.is_none() {
+            println!("--tid needs --nice or --affinity");
+            return;
+        }
+        if let Some(&nice) = nice {
+            match manager.set_thread_nice(pid, tid, nice) {
+                Ok(()) => println!("Changed nice value of thread {} of process {} to {}", tid, pid, nice),
+                Err(e) => println!("Failed to renice thread {} of process {}: {}", tid, pid, e),
+            }
+        }
+        if let Some(list) = affinity {
+            let cpus = match parse_cpu_list(list) {
+                Ok(cpus) => cpus,
+                Err(e) => {
+                    println!("{}", e);
+                    return;
+                }
+            };
+            match manager.set_thread_affinity(pid, tid, &cpus) {
+                Ok(()) => println!("Set affinity of thread {} of process {} to {}", tid, pid, format_cpu_list(&cpus)),
+                Err(e) => println!("Failed to set affinity of thread {} of process {}: {}", tid, pid, e),
+            }
+        }
+        return;
+    }
+
+    // CPU usage is measured between two listings.
+    let _ = manager.threads(pid);
+    thread::sleep(Duration::from_millis(250));
+    let threads = match manager.threads(pid) {
+        Ok(threads) => threads,
+        Err(e) => {
+            println!("Failed to list threads of process {}: {}", pid, e);
+            return;
+        }
+    };
+    if matches.get_flag("json") {
+        match serde_json::to_string_pretty(&threads) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Failed to encode threads of process {}: {}", pid, e),
+        }
+        return;
+    }
+    println!("{:>8}  {:<16}  {:<10}  {:>7}  {:>4}  {:>4}  AFFINITY", "TID", "NAME", "STATE", "CPU%", "LAST", "NICE");
+    for t in &threads {
+        println!(
+            "{:>8}  {:<16}  {:<10}  {:>7.2}  {:>4}  {:>4}  {}",
+            t.tid,
+            t.name,
+            t.state.to_string(),
+            t.cpu_usage,
+            t.last_cpu,
+            t.nice,
+            format_cpu_list(&t.affinity)
+        );
+    }
+}
+
+fn run_files(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let json = matches.get_flag("json");
+    if let Some(path) = matches.get_one::<String>("path") {
+        let holders = manager.holders_of(path.as_ref());
+        if json {
+            match serde_json::to_string_pretty(&holders) {
+                Ok(json) => println!("{}", json),
+                Err(e) => println!("Failed to encode holders of {}: {}", path, e),
+            }
+        } else if holders.is_empty() {
+            println!("No process has {} open", path);
         } else {
-            println!("Alerts:");
-            for alert in alerts {
-                println!("{}", alert);
+            for h in &holders {
+                println!("[{}] {} fd {}", h.pid, h.name, h.fd);
             }
         }
-    } else if matches.get_flag("history") {
-        println!("History:\n{}", manager.show_history());
+        return;
+    }
+
+    let pid_str = matches.get_one::<String>("pid").expect("required without --path");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let mut files = match manager.open_files(pid) {
+        Ok(files) => files,
+        Err(e) => {
+            println!("Failed to list open files of process {}: {}", pid, e);
+            return;
+        }
+    };
+    let summary = files.to_string();
+    if let Some(kind) = matches.get_one::<String>("kind").and_then(|k| k.parse::<FdKind>().ok()) {
+        files.files.retain(|f| f.kind == kind);
+    }
+    if json {
+        match serde_json::to_string_pretty(&files) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Failed to encode open files of process {}: {}", pid, e),
+        }
+        return;
+    }
+    println!("{:>5}  {:<10}  {:>12}  TARGET", "FD", "KIND", "SIZE");
+    for f in &files.files {
+        let size = f.size.map_or(String::new(), |s| s.to_string());
+        println!("{:>5}  {:<10}  {:>12}  {}", f.fd, f.kind.as_str(), size, f.target);
+    }
+    println!("Process {}: {}", pid, summary);
+}
+
+fn run_ports(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let connections: Vec<Connection> = if let Some(pid_str) = matches.get_one::<String>("pid") {
+        let Ok(pid) = pid_str.parse::<usize>() else {
+            println!("Invalid PID: {}", pid_str);
+            return;
+        };
+        match manager.connections(pid) {
+            Ok(connections) => connections,
+            Err(e) => {
+                println!("Failed to list sockets of process {}: {}", pid, e);
+                return;
+            }
+        }
+    } else if let Some(&port) = matches.get_one::<u16>("port") {
+        manager.who_listens(port)
+    } else if matches.get_flag("all") {
+        manager.all_connections()
     } else {
-        println!("No command provided. Run with --help to see options.");
+        manager
+            .all_connections()
+            .into_iter()
+            .filter(|c| c.is_listening() && c.local.is_some())
+            .collect()
+    };
+
+    if matches.get_flag("json") {
+        match serde_json::to_string_pretty(&connections) {
+            Ok(json) => println!("{}", json),
+            Err(e) => println!("Failed to encode sockets: {}", e),
+        }
+        return;
+    }
+    if connections.is_empty() {
+        println!("No sockets found.");
+        return;
+    }
+    println!("{:<5} {:<10} {:<40} {:<40} PROCESS", "PROTO", "STATE", "LOCAL", "REMOTE");
+    for c in &connections {
+        println!("{}", c);
+    }
+}
+
+fn run_memory(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    match manager.memory_usage(pid) {
+        Ok(usage) => println!("{}", usage),
+        Err(e) => {
+            println!("Failed to read memory of process {}: {}", pid, e);
+            return;
+        }
+    }
+    let Some(&limit) = matches.get_one::<usize>("mappings") else {
+        return;
+    };
+    match manager.top_mappings(pid, limit) {
+        Ok(mappings) => {
+            println!();
+            println!("{:>10}  {:>10}  {:>10}  {:>10}  MAPPING", "PSS KB", "RSS KB", "USS KB", "SWAP KB");
+            for m in mappings {
+                println!(
+                    "{:>10}  {:>10}  {:>10}  {:>10}  {}",
+                    m.usage.pss / 1024,
+                    m.usage.rss / 1024,
+                    m.usage.uss / 1024,
+                    m.usage.swap / 1024,
+                    m.path
+                );
+            }
+        }
+        Err(e) => println!("Failed to read mappings of process {}: {}", pid, e),
+    }
+}
+
+fn run_affinity(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let Some(list) = matches.get_one::<String>("cpus") else {
+        match manager.get_affinity(pid) {
+            Ok(cpus) => println!("Process {} may run on CPUs {}", pid, format_cpu_list(&cpus)),
+            Err(e) => println!("Failed to get affinity of process {}: {}", pid, e),
+        }
+        return;
+    };
+    let cpus = match parse_cpu_list(list) {
+        Ok(cpus) => cpus,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    match manager.set_affinity(pid, &cpus) {
+        Ok(()) => println!("Set affinity of process {} to CPUs {}", pid, format_cpu_list(&cpus)),
+        Err(e) => println!("Failed to set affinity of process {}: {}", pid, e),
+    }
+}
+
+fn run_sched(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let Some(policy) = matches.get_one::<String>("policy").and_then(|p| p.parse::<SchedPolicy>().ok()) else {
+        match manager.get_scheduling(pid) {
+            Ok(sched) => println!("Process {}: policy {}, real-time priority {}", pid, sched.policy, sched.priority),
+            Err(e) => println!("Failed to get scheduling of process {}: {}", pid, e),
+        }
+        return;
+    };
+    // Real-time policies need a priority; default to the lowest one.
+    let priority = matches
+        .get_one::<u32>("priority")
+        .copied()
+        .unwrap_or(if policy.is_realtime() { 1 } else { 0 });
+    match manager.set_scheduling(pid, policy, priority) {
+        Ok(()) => println!("Set scheduling of process {} to {} with priority {}", pid, policy, priority),
+        Err(e) => println!("Failed to set scheduling of process {}: {}", pid, e),
+    }
+}
+
+fn run_ionice(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let Some(class) = matches.get_one::<String>("class").and_then(|c| c.parse::<IoClass>().ok()) else {
+        match manager.get_io_priority(pid) {
+            Ok(prio) => println!("Process {}: I/O priority {}", pid, prio),
+            Err(e) => println!("Failed to get I/O priority of process {}: {}", pid, e),
+        }
+        return;
+    };
+    // Same default level as ionice(1).
+    let level = matches
+        .get_one::<u8>("level")
+        .copied()
+        .unwrap_or(if class.has_level() { 4 } else { 0 });
+    if matches.get_flag("pgrp") {
+        match manager.set_group_io_priority(pid, class, level) {
+            Ok(()) => println!("Set I/O priority of process group {} to {} with level {}", pid, class, level),
+            Err(e) => println!("Failed to set I/O priority of process group {}: {}", pid, e),
+        }
+        return;
+    }
+    match manager.set_io_priority(pid, class, level) {
+        Ok(()) => println!("Set I/O priority of process {} to {} with level {}", pid, class, level),
+        Err(e) => println!("Failed to set I/O priority of process {}: {}", pid, e),
+    }
+}
+
+fn run_limits(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let Some(settings) = matches.get_many::<String>("set") else {
+        match manager.limits(pid) {
+            Ok(limits) => {
+                println!("{:<12} {:>20} {:>20} {:<12} DESCRIPTION", "RESOURCE", "SOFT", "HARD", "UNITS");
+                for l in limits {
+                    println!(
+                        "{:<12} {:>20} {:>20} {:<12} {}",
+                        l.resource,
+                        l.soft_label(),
+                        l.hard_label(),
+                        l.resource.units(),
+                        l.resource.description()
+                    );
+                }
+            }
+            Err(e) => println!("Failed to read limits of process {}: {}", pid, e),
+        }
+        return;
+    };
+    for setting in settings {
+        let Some((name, spec)) = setting.split_once('=') else {
+            println!("Invalid limit '{}': expected RESOURCE=SOFT[:HARD]", setting);
+            continue;
+        };
+        // Partial values like `1024:` keep the other side, so start from
+        // the current limit.
+        let limit = name
+            .parse::<Resource>()
+            .and_then(|resource| manager.get_limit(pid, resource))
+            .and_then(|current| current.with_spec(spec));
+        let result = limit.and_then(|l| manager.set_limit(pid, l.resource, l.soft, l.hard).map(|()| l));
+        match result {
+            Ok(l) => println!("Set {} of process {} to {}", l.resource, pid, l),
+            Err(e) => println!("Failed to set limit '{}' of process {}: {}", setting, pid, e),
+        }
+    }
+}
+
+fn run_cgroup(manager: &mut ProcessManager, matches: &ArgMatches) {
+    if let Some(root) = matches.get_one::<String>("root") {
+        manager.set_cgroup_root(root);
+    }
+    match matches.subcommand() {
+        Some(("show", sub)) => {
+            let target = sub.get_one::<String>("target").expect("required");
+            let path = match target.parse::<usize>() {
+                Ok(pid) => match manager.cgroup_of(pid) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        println!("Failed to get cgroup of process {}: {}", pid, e);
+                        return;
+                    }
+                },
+                Err(_) => target.clone(),
+            };
+            match manager.cgroup_info(&path) {
+                Ok(info) => println!("{}", info),
+                Err(e) => println!("Failed to read cgroup {}: {}", path, e),
+            }
+        }
+        Some(("create", sub)) => {
+            let path = sub.get_one::<String>("path").expect("required");
+            match manager.create_cgroup(path) {
+                Ok(()) => println!("Created cgroup {}", path),
+                Err(e) => println!("Failed to create cgroup {}: {}", path, e),
+            }
+        }
+        Some(("move", sub)) => {
+            let pid_str = sub.get_one::<String>("pid").expect("required");
+            let path = sub.get_one::<String>("path").expect("required");
+            let Ok(pid) = pid_str.parse::<usize>() else {
+                println!("Invalid PID: {}", pid_str);
+                return;
+            };
+            match manager.move_to_cgroup(pid, path) {
+                Ok(()) => println!("Moved process {} to cgroup {}", pid, path),
+                Err(e) => println!("Failed to move process {} to cgroup {}: {}", pid, path, e),
+            }
+        }
+        Some(("limit", sub)) => {
+            let path = sub.get_one::<String>("path").expect("required");
+            let value = sub.get_one::<String>("value").expect("required");
+            let Ok(limit) = sub.get_one::<String>("resource").expect("required").parse::<CgroupLimit>() else {
+                return;
+            };
+            match manager.set_cgroup_limit(path, limit, value) {
+                Ok(()) => println!("Set {} of cgroup {} to {}", limit.file(), path, value),
+                Err(e) => println!("Failed to set {} of cgroup {}: {}", limit.file(), path, e),
+            }
+        }
+        _ => {}
     }
 }
 
+fn run_oom(manager: &mut ProcessManager, matches: &ArgMatches) {
+    let pid_str = matches.get_one::<String>("pid").expect("required");
+    let Ok(pid) = pid_str.parse::<usize>() else {
+        println!("Invalid PID: {}", pid_str);
+        return;
+    };
+    let Some(&adj) = matches.get_one::<i32>("adj") else {
+        match (manager.oom_score(pid), manager.oom_score_adj(pid)) {
+            (Ok(score), Ok(adj)) => println!("Process {}: oom_score {}, oom_score_adj {}", pid, score, adj),
+            (Err(e), _) | (_, Err(e)) => println!("Failed to read OOM score of process {}: {}", pid, e),
+        }
+        return;
+    };
+    match manager.set_oom_score_adj(pid, adj) {
+        Ok(()) => println!("Set OOM score adjustment of process {} to {}", pid, adj),
+        Err(e) => println!("Failed to set OOM score adjustment of process {}: {}", pid, e),
+    }
+}
@@ -0,0 +1,173 @@
+// src/affinity.rs for lpm-core
+//
+// CPU affinity through sched_getaffinity(2)/sched_setaffinity(2), and the
+// kernel's cpulist syntax (`0-3,8`).
+
+use std::fs;
+use std::io;
+use std::mem;
+
+use crate::{error, procfs, ActionKind, LpmError, ProcessManager};
+
+/// Parses a cpulist such as `0-3,8` or `1,3-5` into sorted, distinct CPU
+/// numbers.
+pub fn parse_cpu_list(s: &str) -> Result<Vec<usize>, LpmError> {
+    let invalid = || LpmError::InvalidInput(format!("invalid CPU list '{}'", s));
+    let mut cpus = Vec::new();
+    for part in s.split(',').map(str::trim) {
+        let (first, last): (usize, usize) = match part.split_once('-') {
+            Some((a, b)) => (a.trim().parse().map_err(|_| invalid())?, b.trim().parse().map_err(|_| invalid())?),
+            None => {
+                let cpu = part.parse().map_err(|_| invalid())?;
+                (cpu, cpu)
+            }
+        };
+        if first > last || last >= libc::CPU_SETSIZE as usize {
+            return Err(invalid());
+        }
+        cpus.extend(first..=last);
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Ok(cpus)
+}
+
+/// Formats CPUs as a kernel-style list such as `0-3,6`.
+pub fn format_cpu_list(cpus: &[usize]) -> String {
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < cpus.len() {
+        let start = cpus[i];
+        while i + 1 < cpus.len() && cpus[i + 1] == cpus[i] + 1 {
+            i += 1;
+        }
+        if cpus[i] == start {
+            parts.push(start.to_string());
+        } else {
+            parts.push(format!("{}-{}", start, cpus[i]));
+        }
+        i += 1;
+    }
+    parts.join(",")
+}
+
+/// CPUs that are online, from /sys; falls back to `0..nproc`.
+pub fn online_cpus() -> Vec<usize> {
+    fs::read_to_string("/sys/devices/system/cpu/online")
+        .ok()
+        .and_then(|list| parse_cpu_list(list.trim()).ok())
+        .unwrap_or_else(|| {
+            let n = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+            (0..n.max(1) as usize).collect()
+        })
+}
+
+/// The CPUs task `tid` may run on.
+pub(crate) fn read_affinity(tid: libc::pid_t) -> io::Result<Vec<usize>> {
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(tid, mem::size_of::<libc::cpu_set_t>(), &mut set) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect())
+}
+
+/// Restricts task `tid` to `cpus`.
+pub(crate) fn write_affinity(tid: libc::pid_t, cpus: &[usize]) -> Result<(), LpmError> {
+    if cpus.is_empty() {
+        return Err(LpmError::InvalidInput("CPU list is empty".to_string()));
+    }
+    let mut set: libc::cpu_set_t = unsafe { mem::zeroed() };
+    for &cpu in cpus {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(LpmError::InvalidInput(format!("CPU {} is out of range", cpu)));
+        }
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    error::check_os(
+        unsafe { libc::sched_setaffinity(tid, mem::size_of::<libc::cpu_set_t>(), &set) },
+        tid as u32,
+    )
+}
+
+impl ProcessManager {
+    /// The CPUs the main thread of `pid` may run on.
+    pub fn get_affinity(&mut self, pid: usize) -> Result<Vec<usize>, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        read_affinity(raw).map_err(|e| LpmError::from_io(e, pid32))
+    }
+
+    /// Restricts every thread of `pid` to `cpus`. sched_setaffinity only
+    /// affects one thread, so threads that already exist would otherwise
+    /// keep their old mask; threads started later inherit it from their
+    /// creator.
+    pub fn set_affinity(&mut self, pid: usize, cpus: &[usize]) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let old = self.get_affinity(pid).ok();
+        let result = self.check_target(pid).and_then(|raw| {
+            write_affinity(raw, cpus)?;
+            let tids = procfs::list_tasks(raw).map_err(|e| LpmError::from_io(e, pid as u32))?;
+            for tid in tids.into_iter().filter(|&tid| tid != raw) {
+                match write_affinity(tid, cpus) {
+                    // The thread exited after the task list was read.
+                    Ok(()) | Err(LpmError::NoSuchProcess(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        });
+        let mut params = vec![("cpus", format_cpu_list(cpus))];
+        if let Some(old) = old {
+            params.push(("old", format_cpu_list(&old)));
+        }
+        self.record(ActionKind::Affinity, pid, name, &params, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_invalid(s: &str) -> bool {
+        matches!(parse_cpu_list(s), Err(LpmError::InvalidInput(_)))
+    }
+
+    #[test]
+    fn parses_ranges_and_single_cpus() {
+        assert_eq!(parse_cpu_list("0-3,8").unwrap(), [0, 1, 2, 3, 8]);
+        assert_eq!(parse_cpu_list("5").unwrap(), [5]);
+        assert_eq!(parse_cpu_list("2-2").unwrap(), [2]);
+        assert_eq!(parse_cpu_list(" 8 , 0 - 1 ").unwrap(), [0, 1, 8]);
+        assert_eq!(parse_cpu_list("1-3,2-4,3").unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn rejects_bad_lists() {
+        assert!(is_invalid(""));
+        assert!(is_invalid("3-1"));
+        assert!(is_invalid("0,"));
+        assert!(is_invalid("a-b"));
+        assert!(is_invalid("-1"));
+        assert!(is_invalid("0-"));
+        assert!(is_invalid(&libc::CPU_SETSIZE.to_string()));
+        assert!(is_invalid(&format!("0-{}", libc::CPU_SETSIZE)));
+    }
+
+    #[test]
+    fn formats_kernel_style_lists() {
+        assert_eq!(format_cpu_list(&[0, 1, 2, 3, 8]), "0-3,8");
+        assert_eq!(format_cpu_list(&[5]), "5");
+        assert_eq!(format_cpu_list(&[1, 3, 4, 6]), "1,3-4,6");
+        assert_eq!(format_cpu_list(&[]), "");
+    }
+
+    #[test]
+    fn round_trips() {
+        for list in ["0-3,8", "5", "0,2,4-7,63", "0-1023"] {
+            assert_eq!(format_cpu_list(&parse_cpu_list(list).unwrap()), list);
+        }
+    }
+}
@@ -0,0 +1,706 @@
+// src/alerts.rs for lpm-core
+//
+// Rule-based alerting. Rules are evaluated against every refresh of the
+// process list; an alert fires only after its condition has held for the
+// rule's minimum duration and resolves only once the value has recovered
+// past the clear threshold. Firing alerts can run remediation actions.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{LpmError, ProcessManager, ProcessSnapshot, Remediation};
+
+/// A per-process value that rules can watch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// CPU usage in percent of one core.
+    Cpu,
+    /// Resident memory in bytes.
+    Memory,
+    /// Virtual memory in bytes.
+    VirtualMemory,
+    Threads,
+    /// Bytes read from disk since the previous refresh.
+    DiskRead,
+    /// Bytes written to disk since the previous refresh.
+    DiskWrite,
+}
+
+impl Metric {
+    pub const ALL: &'static [Metric] = &[
+        Metric::Cpu,
+        Metric::Memory,
+        Metric::VirtualMemory,
+        Metric::Threads,
+        Metric::DiskRead,
+        Metric::DiskWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu",
+            Metric::Memory => "memory",
+            Metric::VirtualMemory => "virtual_memory",
+            Metric::Threads => "threads",
+            Metric::DiskRead => "disk_read",
+            Metric::DiskWrite => "disk_write",
+        }
+    }
+
+    pub fn value(&self, p: &ProcessSnapshot) -> f64 {
+        match self {
+            Metric::Cpu => p.cpu_usage as f64,
+            Metric::Memory => p.memory as f64,
+            Metric::VirtualMemory => p.virtual_memory as f64,
+            Metric::Threads => p.threads as f64,
+            Metric::DiskRead => p.disk_io.read_bytes as f64,
+            Metric::DiskWrite => p.disk_io.written_bytes as f64,
+        }
+    }
+
+    /// Formats `value` in this metric's unit, e.g. `85.0%` or `512.0 MiB`.
+    pub fn format(&self, value: f64) -> String {
+        match self {
+            Metric::Cpu => format!("{:.1}%", value),
+            Metric::Threads => format!("{}", value as u64),
+            _ => format!("{:.1} MiB", value / (1024.0 * 1024.0)),
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Metric {
+    type Err = LpmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        Metric::ALL
+            .iter()
+            .copied()
+            .find(|m| m.as_str() == s)
+            .ok_or_else(|| LpmError::InvalidInput(format!("unknown metric '{}'", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = "<")]
+    Below,
+}
+
+impl Comparison {
+    fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::Below => value < threshold,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Comparison::Above => ">",
+            Comparison::Below => "<",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Critical => "CRITICAL",
+        })
+    }
+}
+
+/// Restricts a rule to some processes. Empty fields match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessMatcher {
+    /// Case-insensitive substring of the process name.
+    pub name: Option<String>,
+    /// Real or effective user, as a name or numeric uid.
+    pub user: Option<String>,
+}
+
+impl ProcessMatcher {
+    pub fn matches(&self, p: &ProcessSnapshot) -> bool {
+        let name_ok = self
+            .name
+            .as_ref()
+            .is_none_or(|n| p.name.to_lowercase().contains(&n.to_lowercase()));
+        let user_ok = self.user.as_ref().is_none_or(|u| p.matches_user(u));
+        name_ok && user_ok
+    }
+}
+
+/// A declarative alert condition: `metric comparison threshold` sustained for
+/// at least `duration` on a process selected by `matcher`.
+///
+/// Rule names identify rules and should be unique.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: Metric,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// The value must recover past this before a firing alert resolves.
+    /// Defaults to `threshold` (no hysteresis).
+    #[serde(default)]
+    pub clear_threshold: Option<f64>,
+    /// How long the condition must hold before the alert fires.
+    #[serde(default, rename = "for_secs", with = "duration_secs")]
+    pub duration: Duration,
+    #[serde(default, rename = "match")]
+    pub matcher: ProcessMatcher,
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+    /// Run in order against the process whenever the alert is firing and the
+    /// cooldown for that process has passed.
+    #[serde(default)]
+    pub actions: Vec<Remediation>,
+    /// Minimum time between two runs of `actions` on the same process.
+    #[serde(default = "default_cooldown", rename = "cooldown_secs", with = "duration_secs")]
+    pub cooldown: Duration,
+}
+
+fn default_severity() -> Severity {
+    Severity::Warning
+}
+
+fn default_cooldown() -> Duration {
+    Duration::from_secs(60)
+}
+
+impl AlertRule {
+    /// The rules used when none are configured: sustained high CPU and
+    /// resident memory above 500 MiB.
+    pub fn defaults() -> Vec<AlertRule> {
+        vec![
+            AlertRule {
+                name: "high-cpu".to_string(),
+                metric: Metric::Cpu,
+                comparison: Comparison::Above,
+                threshold: 80.0,
+                clear_threshold: Some(70.0),
+                duration: Duration::from_secs(5),
+                matcher: ProcessMatcher::default(),
+                severity: Severity::Warning,
+                actions: Vec::new(),
+                cooldown: default_cooldown(),
+            },
+            AlertRule {
+                name: "high-memory".to_string(),
+                metric: Metric::Memory,
+                comparison: Comparison::Above,
+                threshold: 500.0 * 1024.0 * 1024.0,
+                clear_threshold: Some(450.0 * 1024.0 * 1024.0),
+                duration: Duration::from_secs(5),
+                matcher: ProcessMatcher::default(),
+                severity: Severity::Warning,
+                actions: Vec::new(),
+                cooldown: default_cooldown(),
+            },
+        ]
+    }
+
+    /// Reads a JSON array of rules.
+    pub fn load(path: impl AsRef<Path>) -> Result<Vec<AlertRule>, LpmError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            LpmError::InvalidInput(format!("invalid alert rules in '{}': {}", path.display(), e))
+        })
+    }
+
+    fn breached(&self, value: f64) -> bool {
+        self.comparison.holds(value, self.threshold)
+    }
+
+    fn recovered(&self, value: f64) -> bool {
+        !self.comparison.holds(value, self.clear_threshold.unwrap_or(self.threshold))
+    }
+}
+
+mod duration_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(d.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        let secs = f64::deserialize(d)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStatus {
+    Firing,
+    /// Reported once, on the refresh where the value recovered or the
+    /// process exited, and then dropped.
+    Resolved,
+}
+
+impl fmt::Display for AlertStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AlertStatus::Firing => "firing",
+            AlertStatus::Resolved => "resolved",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    pub rule: String,
+    pub severity: Severity,
+    pub status: AlertStatus,
+    pub pid: u32,
+    /// Process start time, distinguishing reused pids.
+    pub start_time: u64,
+    pub name: String,
+    pub user: Option<String>,
+    pub metric: Metric,
+    /// The most recent value seen for the process.
+    pub value: f64,
+    pub threshold: f64,
+    /// When the condition first held (the start of the sustained period).
+    pub first_seen: DateTime<Local>,
+    /// The last refresh on which the condition held.
+    pub last_seen: DateTime<Local>,
+    /// Remediation run on this refresh, with outcomes (`renice 10: ok`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<String>,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {} {} ({}): {} = {} (threshold {}) since {}",
+            self.severity,
+            self.status,
+            self.rule,
+            self.pid,
+            self.name,
+            self.metric,
+            self.metric.format(self.value),
+            self.metric.format(self.threshold),
+            self.first_seen.format("%H:%M:%S"),
+        )?;
+        for action in &self.actions {
+            write!(f, " [auto: {}]", action)?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies a process incarnation, so a reused pid starts fresh.
+type ProcessKey = (u32, u64);
+
+#[derive(Debug, Clone)]
+enum RuleState {
+    /// The condition holds but not yet for the rule's duration.
+    Pending { since: DateTime<Local> },
+    Firing(Alert),
+}
+
+/// Tracks rule state across refreshes.
+#[derive(Debug, Clone, Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    /// Indexed like `rules`.
+    states: Vec<HashMap<ProcessKey, RuleState>>,
+    /// When each rule last ran its actions on a process; indexed like `rules`.
+    last_action: Vec<HashMap<ProcessKey, DateTime<Local>>>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let states = vec![HashMap::new(); rules.len()];
+        let last_action = states.iter().map(|_| HashMap::new()).collect();
+        Self { rules, states, last_action }
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Replaces the rules and forgets all pending and firing alerts.
+    pub fn set_rules(&mut self, rules: Vec<AlertRule>) {
+        *self = Self::new(rules);
+    }
+
+    /// Feeds one refresh of the process list taken at `now` and returns the
+    /// firing alerts plus those that resolved on this refresh, most severe
+    /// first.
+    pub fn evaluate(&mut self, processes: &[ProcessSnapshot], now: DateTime<Local>) -> Vec<Alert> {
+        let mut out = Vec::new();
+        for (rule, states) in self.rules.iter().zip(&mut self.states) {
+            let mut seen = HashMap::with_capacity(states.len());
+            for p in processes.iter().filter(|p| rule.matcher.matches(p)) {
+                let key = (p.pid, p.start_time);
+                let value = rule.metric.value(p);
+                let next = match states.remove(&key) {
+                    Some(RuleState::Firing(mut alert)) => {
+                        alert.value = value;
+                        if rule.recovered(value) {
+                            alert.status = AlertStatus::Resolved;
+                            out.push(alert);
+                            continue;
+                        }
+                        if rule.breached(value) {
+                            alert.last_seen = now;
+                        }
+                        RuleState::Firing(alert)
+                    }
+                    _ if !rule.breached(value) => continue,
+                    Some(RuleState::Pending { since }) => RuleState::Pending { since },
+                    None => RuleState::Pending { since: now },
+                };
+                let next = match next {
+                    RuleState::Pending { since }
+                        if (now - since).to_std().unwrap_or_default() >= rule.duration =>
+                    {
+                        RuleState::Firing(Alert {
+                            rule: rule.name.clone(),
+                            severity: rule.severity,
+                            status: AlertStatus::Firing,
+                            pid: p.pid,
+                            start_time: p.start_time,
+                            name: p.name.clone(),
+                            user: p.user.clone(),
+                            metric: rule.metric,
+                            value,
+                            threshold: rule.threshold,
+                            first_seen: since,
+                            last_seen: now,
+                            actions: Vec::new(),
+                        })
+                    }
+                    other => other,
+                };
+                if let RuleState::Firing(alert) = &next {
+                    out.push(alert.clone());
+                }
+                seen.insert(key, next);
+            }
+            // Whatever is left belongs to processes that exited or no longer match.
+            for state in states.drain().map(|(_, s)| s) {
+                if let RuleState::Firing(mut alert) = state {
+                    alert.status = AlertStatus::Resolved;
+                    out.push(alert);
+                }
+            }
+            *states = seen;
+        }
+        out.sort_by(|a, b| {
+            (a.status, std::cmp::Reverse(a.severity), a.pid)
+                .cmp(&(b.status, std::cmp::Reverse(b.severity), b.pid))
+        });
+        out
+    }
+
+    /// The remediation actions to run for `alert` now, or nothing while the
+    /// rule's cooldown for the process has not passed. Returning actions
+    /// starts a new cooldown.
+    pub(crate) fn due_actions(&mut self, alert: &Alert, now: DateTime<Local>) -> Vec<Remediation> {
+        let Some(index) = self.rules.iter().position(|r| r.name == alert.rule) else {
+            return Vec::new();
+        };
+        let rule = &self.rules[index];
+        let last_action = &mut self.last_action[index];
+        let cooled_down = |t: &DateTime<Local>| (now - *t).to_std().unwrap_or_default() >= rule.cooldown;
+        last_action.retain(|_, t| !cooled_down(t));
+        if alert.status != AlertStatus::Firing || rule.actions.is_empty() {
+            return Vec::new();
+        }
+        let key = (alert.pid, alert.start_time);
+        if last_action.contains_key(&key) {
+            return Vec::new();
+        }
+        last_action.insert(key, now);
+        rule.actions.clone()
+    }
+
+    /// Alerts that are currently firing.
+    pub fn firing(&self) -> Vec<&Alert> {
+        self.states
+            .iter()
+            .flat_map(|s| s.values())
+            .filter_map(|s| match s {
+                RuleState::Firing(alert) => Some(alert),
+                RuleState::Pending { .. } => None,
+            })
+            .collect()
+    }
+
+    /// The longest minimum duration of any rule; how long a one-shot check
+    /// has to sample before every rule can fire.
+    pub fn longest_duration(&self) -> Duration {
+        self.rules.iter().map(|r| r.duration).max().unwrap_or_default()
+    }
+}
+
+impl ProcessManager {
+    pub fn alert_rules(&self) -> &[AlertRule] {
+        self.alerts.rules()
+    }
+
+    pub fn set_alert_rules(&mut self, rules: Vec<AlertRule>) {
+        self.alerts.set_rules(rules);
+    }
+
+    pub fn alert_engine(&self) -> &AlertEngine {
+        &self.alerts
+    }
+
+    /// Refreshes the process list, runs it through the alert rules and
+    /// carries out the actions of firing rules. Call this periodically;
+    /// sustained conditions only fire after enough refreshes have shown them
+    /// for the rule's duration.
+    pub fn check_alerts(&mut self) -> Vec<Alert> {
+        let processes = self.list_processes();
+        let now = Local::now();
+        let mut alerts = self.alerts.evaluate(&processes, now);
+        for alert in &mut alerts {
+            for action in self.alerts.due_actions(alert, now) {
+                let outcome = match self.remediate(alert, &action) {
+                    Ok(()) => "ok".to_string(),
+                    Err(e) => format!("failed: {}", e),
+                };
+                alert.actions.push(format!("{}: {}", action, outcome));
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(secs: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap() + chrono::Duration::seconds(secs)
+    }
+
+    fn process(pid: u32, start_time: u64, cpu: f32) -> ProcessSnapshot {
+        ProcessSnapshot { start_time, cpu_usage: cpu, ..ProcessSnapshot::fixture(pid, "worker") }
+    }
+
+    fn cpu_rule(name: &str, severity: Severity, duration: u64) -> AlertRule {
+        AlertRule {
+            name: name.to_string(),
+            metric: Metric::Cpu,
+            comparison: Comparison::Above,
+            threshold: 80.0,
+            clear_threshold: Some(70.0),
+            duration: Duration::from_secs(duration),
+            matcher: ProcessMatcher::default(),
+            severity,
+            actions: Vec::new(),
+            cooldown: Duration::from_secs(60),
+        }
+    }
+
+    fn engine(duration: u64) -> AlertEngine {
+        AlertEngine::new(vec![cpu_rule("high-cpu", Severity::Warning, duration)])
+    }
+
+    fn statuses(alerts: &[Alert]) -> Vec<(AlertStatus, u32, u64)> {
+        alerts.iter().map(|a| (a.status, a.pid, a.start_time)).collect()
+    }
+
+    #[test]
+    fn short_spikes_stay_pending() {
+        let mut engine = engine(5);
+        assert!(engine.evaluate(&[process(10, 1, 90.0)], at(0)).is_empty());
+        assert!(engine.evaluate(&[process(10, 1, 90.0)], at(4)).is_empty());
+        // Dropping below the threshold resets the sustained period.
+        assert!(engine.evaluate(&[process(10, 1, 50.0)], at(5)).is_empty());
+        assert!(engine.evaluate(&[process(10, 1, 90.0)], at(6)).is_empty());
+        assert!(engine.evaluate(&[process(10, 1, 90.0)], at(10)).is_empty());
+        assert!(engine.firing().is_empty());
+
+        let alerts = engine.evaluate(&[process(10, 1, 95.0)], at(11));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Firing, 10, 1)]);
+        assert_eq!(alerts[0].first_seen, at(6));
+        assert_eq!(alerts[0].last_seen, at(11));
+        assert_eq!(alerts[0].value, 95.0);
+        assert_eq!(engine.firing().len(), 1);
+    }
+
+    #[test]
+    fn firing_alerts_resolve_below_the_clear_threshold() {
+        let mut engine = engine(5);
+        engine.evaluate(&[process(10, 1, 90.0)], at(0));
+        assert_eq!(engine.evaluate(&[process(10, 1, 90.0)], at(5)).len(), 1);
+
+        let alerts = engine.evaluate(&[process(10, 1, 75.0)], at(6));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Firing, 10, 1)]);
+        assert_eq!(alerts[0].value, 75.0);
+        assert_eq!(alerts[0].last_seen, at(5));
+
+        let alerts = engine.evaluate(&[process(10, 1, 65.0)], at(7));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Resolved, 10, 1)]);
+        assert_eq!(alerts[0].value, 65.0);
+        assert!(engine.evaluate(&[process(10, 1, 65.0)], at(8)).is_empty());
+        assert!(engine.firing().is_empty());
+    }
+
+    #[test]
+    fn reused_pids_start_pending() {
+        let mut engine = engine(5);
+        engine.evaluate(&[process(10, 1, 90.0)], at(0));
+        engine.evaluate(&[process(10, 1, 90.0)], at(5));
+
+        let alerts = engine.evaluate(&[process(10, 2, 90.0)], at(6));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Resolved, 10, 1)]);
+        assert!(engine.evaluate(&[process(10, 2, 90.0)], at(10)).is_empty());
+
+        let alerts = engine.evaluate(&[process(10, 2, 90.0)], at(11));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Firing, 10, 2)]);
+        assert_eq!(alerts[0].first_seen, at(6));
+    }
+
+    #[test]
+    fn exited_processes_resolve_once() {
+        let mut engine = engine(0);
+        assert_eq!(engine.evaluate(&[process(10, 1, 90.0), process(11, 1, 90.0)], at(0)).len(), 2);
+
+        let alerts = engine.evaluate(&[process(11, 1, 90.0)], at(1));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Firing, 11, 1), (AlertStatus::Resolved, 10, 1)]);
+        assert_eq!(alerts[1].last_seen, at(0));
+
+        let alerts = engine.evaluate(&[process(11, 1, 90.0)], at(2));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Firing, 11, 1)]);
+        assert!(engine.evaluate(&[], at(3)).iter().all(|a| a.status == AlertStatus::Resolved));
+        assert!(engine.evaluate(&[], at(4)).is_empty());
+    }
+
+    #[test]
+    fn pending_processes_that_exit_are_forgotten() {
+        let mut engine = engine(5);
+        engine.evaluate(&[process(10, 1, 90.0)], at(0));
+        assert!(engine.evaluate(&[], at(1)).is_empty());
+        assert!(engine.evaluate(&[process(10, 1, 90.0)], at(5)).is_empty());
+    }
+
+    #[test]
+    fn orders_by_status_severity_and_pid() {
+        let mut engine = AlertEngine::new(vec![
+            cpu_rule("warn", Severity::Warning, 0),
+            AlertRule { threshold: 50.0, clear_threshold: None, ..cpu_rule("crit", Severity::Critical, 0) },
+            AlertRule { threshold: 95.0, ..cpu_rule("info", Severity::Info, 0) },
+        ]);
+        let first = [process(3, 1, 90.0), process(1, 1, 60.0), process(2, 1, 99.0), process(4, 1, 99.0)];
+        engine.evaluate(&first, at(0));
+
+        let second = [process(3, 1, 90.0), process(1, 1, 60.0), process(2, 1, 99.0)];
+        let alerts = engine.evaluate(&second, at(1));
+        let order: Vec<(AlertStatus, &str, u32)> =
+            alerts.iter().map(|a| (a.status, a.rule.as_str(), a.pid)).collect();
+        assert_eq!(
+            order,
+            [
+                (AlertStatus::Firing, "crit", 1),
+                (AlertStatus::Firing, "crit", 2),
+                (AlertStatus::Firing, "crit", 3),
+                (AlertStatus::Firing, "warn", 2),
+                (AlertStatus::Firing, "warn", 3),
+                (AlertStatus::Firing, "info", 2),
+                (AlertStatus::Resolved, "crit", 4),
+                (AlertStatus::Resolved, "warn", 4),
+                (AlertStatus::Resolved, "info", 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn matcher_limits_the_processes() {
+        let rule = AlertRule {
+            matcher: ProcessMatcher { name: Some("WORK".to_string()), user: Some("1000".to_string()) },
+            ..cpu_rule("high-cpu", Severity::Warning, 0)
+        };
+        let mut engine = AlertEngine::new(vec![rule]);
+        let other = ProcessSnapshot { cpu_usage: 90.0, ..ProcessSnapshot::fixture(11, "idle") };
+        let alerts = engine.evaluate(&[process(10, 1, 90.0), other], at(0));
+        assert_eq!(statuses(&alerts), [(AlertStatus::Firing, 10, 1)]);
+    }
+
+    /// A firing alert of a rule with one action and a 60 s cooldown.
+    fn firing_with_actions(actions: Vec<Remediation>) -> (AlertEngine, Alert) {
+        let rule = AlertRule { actions, ..cpu_rule("high-cpu", Severity::Warning, 0) };
+        let mut engine = AlertEngine::new(vec![rule]);
+        let alert = engine.evaluate(&[process(10, 1, 90.0)], at(0)).remove(0);
+        (engine, alert)
+    }
+
+    #[test]
+    fn actions_wait_for_the_cooldown() {
+        let (mut engine, alert) = firing_with_actions(vec![Remediation::Suspend]);
+        assert_eq!(engine.due_actions(&alert, at(0)), [Remediation::Suspend]);
+        assert!(engine.due_actions(&alert, at(1)).is_empty());
+        assert!(engine.due_actions(&alert, at(59)).is_empty());
+        assert_eq!(engine.due_actions(&alert, at(60)), [Remediation::Suspend]);
+        assert!(engine.due_actions(&alert, at(61)).is_empty());
+    }
+
+    #[test]
+    fn resolved_alerts_and_empty_rules_start_no_cooldown() {
+        let (mut engine, alert) = firing_with_actions(vec![Remediation::Suspend]);
+        let resolved = Alert { status: AlertStatus::Resolved, ..alert.clone() };
+        assert!(engine.due_actions(&resolved, at(0)).is_empty());
+        assert!(engine.last_action[0].is_empty());
+        assert_eq!(engine.due_actions(&alert, at(1)), [Remediation::Suspend]);
+
+        let (mut engine, alert) = firing_with_actions(Vec::new());
+        assert!(engine.due_actions(&alert, at(0)).is_empty());
+        assert!(engine.last_action[0].is_empty());
+
+        let unknown = Alert { rule: "gone".to_string(), ..alert };
+        assert!(engine.due_actions(&unknown, at(0)).is_empty());
+    }
+
+    #[test]
+    fn reused_pids_have_their_own_cooldown() {
+        let (mut engine, alert) = firing_with_actions(vec![Remediation::Renice { nice: 10 }]);
+        let reused = Alert { start_time: 2, ..alert.clone() };
+        let other = Alert { pid: 11, ..alert.clone() };
+        assert_eq!(engine.due_actions(&alert, at(0)).len(), 1);
+        assert_eq!(engine.due_actions(&reused, at(1)).len(), 1);
+        assert_eq!(engine.due_actions(&other, at(2)).len(), 1);
+        assert!(engine.due_actions(&alert, at(3)).is_empty());
+        assert!(engine.due_actions(&reused, at(3)).is_empty());
+    }
+}
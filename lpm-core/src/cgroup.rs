@@ -0,0 +1,626 @@
+// src/cgroup.rs for lpm-core
+//
+// cgroup v2 inspection and control: usage and limits of a cgroup, creating
+// child cgroups and moving processes into them. Everything goes through
+// files below a configurable root, so a plain directory can stand in for
+// cgroupfs.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use crate::{procfs, ActionKind, LpmError, ProcessManager};
+
+/// Where cgroupfs is usually mounted on a pure cgroup v2 system.
+const DEFAULT_ROOT: &str = "/sys/fs/cgroup";
+
+/// The mount point of the cgroup v2 hierarchy from /proc/self/mountinfo,
+/// which is `/sys/fs/cgroup/unified` on hybrid systems; [`DEFAULT_ROOT`]
+/// if none is mounted.
+pub fn default_cgroup_root() -> PathBuf {
+    fs::read_to_string("/proc/self/mountinfo")
+        .ok()
+        .and_then(|mounts| {
+            mounts.lines().find_map(|line| {
+                let (mount, fs) = line.split_once(" - ")?;
+                if fs.split_whitespace().next() != Some("cgroup2") {
+                    return None;
+                }
+                mount.split_whitespace().nth(4).map(PathBuf::from)
+            })
+        })
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_ROOT))
+}
+
+/// A limit file of a cgroup v2 controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CgroupLimit {
+    /// `cpu.max`: bandwidth as `quota period` in microseconds.
+    Cpu,
+    /// `memory.max`: hard limit in bytes; the OOM killer runs above it.
+    Memory,
+    /// `pids.max`: number of processes and threads.
+    Pids,
+    /// `io.max`: per-device `rbps`, `wbps`, `riops` and `wiops`.
+    Io,
+}
+
+impl CgroupLimit {
+    pub const ALL: &'static [CgroupLimit] = &[CgroupLimit::Cpu, CgroupLimit::Memory, CgroupLimit::Pids, CgroupLimit::Io];
+
+    /// The controller name, e.g. `memory`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CgroupLimit::Cpu => "cpu",
+            CgroupLimit::Memory => "memory",
+            CgroupLimit::Pids => "pids",
+            CgroupLimit::Io => "io",
+        }
+    }
+
+    pub fn file(&self) -> &'static str {
+        match self {
+            CgroupLimit::Cpu => "cpu.max",
+            CgroupLimit::Memory => "memory.max",
+            CgroupLimit::Pids => "pids.max",
+            CgroupLimit::Io => "io.max",
+        }
+    }
+
+    /// Checks a user-supplied value and converts it to what the kernel
+    /// accepts:
+    ///
+    /// - cpu: `max`, a percentage of one CPU such as `50%`, or
+    ///   `quota [period]`
+    /// - memory: `max` or a size with an optional K, M, G or T suffix
+    /// - pids: `max` or a count
+    /// - io: `MAJ:MIN key=value...` with keys rbps, wbps, riops and wiops
+    fn normalize(&self, value: &str) -> Result<String, LpmError> {
+        let value = value.trim();
+        let invalid = || LpmError::InvalidInput(format!("invalid {} limit '{}'", self, value));
+        let number_or_max = |s: &str| s == "max" || s.parse::<u64>().is_ok();
+        match self {
+            CgroupLimit::Cpu => {
+                if let Some(pct) = value.strip_suffix('%') {
+                    let pct: f64 = pct.trim().parse().map_err(|_| invalid())?;
+                    let quota = (pct * 1000.0).round();
+                    if !(quota >= 1000.0 && quota.is_finite()) {
+                        return Err(invalid());
+                    }
+                    return Ok(format!("{} 100000", quota as u64));
+                }
+                let mut parts = value.split_whitespace();
+                let quota = parts.next().filter(|q| number_or_max(q)).ok_or_else(invalid)?;
+                match (parts.next(), parts.next()) {
+                    (None, _) => Ok(quota.to_string()),
+                    (Some(period), None) if period.parse::<u64>().is_ok() => Ok(format!("{} {}", quota, period)),
+                    _ => Err(invalid()),
+                }
+            }
+            CgroupLimit::Memory => {
+                if value == "max" {
+                    return Ok(value.to_string());
+                }
+                let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+                let (digits, suffix) = value.split_at(split);
+                let shift = match suffix.to_ascii_uppercase().as_str() {
+                    "" => 0,
+                    "K" | "KB" => 10,
+                    "M" | "MB" => 20,
+                    "G" | "GB" => 30,
+                    "T" | "TB" => 40,
+                    _ => return Err(invalid()),
+                };
+                let bytes = digits.parse::<u64>().ok().and_then(|n| n.checked_mul(1 << shift)).ok_or_else(invalid)?;
+                Ok(bytes.to_string())
+            }
+            CgroupLimit::Pids => {
+                if number_or_max(value) {
+                    Ok(value.to_string())
+                } else {
+                    Err(invalid())
+                }
+            }
+            CgroupLimit::Io => {
+                let mut parts = value.split_whitespace();
+                let device = parts.next().and_then(|d| d.split_once(':')).ok_or_else(invalid)?;
+                if device.0.parse::<u32>().is_err() || device.1.parse::<u32>().is_err() {
+                    return Err(invalid());
+                }
+                let mut settings = 0;
+                for part in parts {
+                    let (key, v) = part.split_once('=').ok_or_else(invalid)?;
+                    if !matches!(key, "rbps" | "wbps" | "riops" | "wiops") || !number_or_max(v) {
+                        return Err(invalid());
+                    }
+                    settings += 1;
+                }
+                if settings == 0 {
+                    return Err(invalid());
+                }
+                Ok(value.split_whitespace().collect::<Vec<_>>().join(" "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for CgroupLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for CgroupLimit {
+    type Err = LpmError;
+
+    /// Accepts the controller (`memory`) or the file name (`memory.max`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        CgroupLimit::ALL
+            .iter()
+            .copied()
+            .find(|l| l.as_str() == s || l.file() == s)
+            .ok_or_else(|| LpmError::InvalidInput(format!("unknown cgroup limit '{}'", s)))
+    }
+}
+
+/// Usage and limits of one cgroup. Files of controllers that are not
+/// enabled for the cgroup are `None` (or empty).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CgroupInfo {
+    /// Path below the cgroup root, starting with `/`.
+    pub path: String,
+    /// Controllers available to the cgroup, from `cgroup.controllers`.
+    pub controllers: Vec<String>,
+    /// `cpu.max` as the kernel shows it, e.g. `max 100000`.
+    pub cpu_max: Option<String>,
+    /// Total CPU time used, in microseconds.
+    pub cpu_usage_usec: Option<u64>,
+    /// Memory in use, in bytes.
+    pub memory_current: Option<u64>,
+    /// `memory.max`: bytes or `max`.
+    pub memory_max: Option<String>,
+    pub pids_current: Option<u64>,
+    /// `pids.max`: a count or `max`.
+    pub pids_max: Option<String>,
+    /// One `MAJ:MIN rbps=... wbps=...` line per limited device.
+    pub io_max: Vec<String>,
+    /// Processes directly in this cgroup, from `cgroup.procs`.
+    pub procs: Vec<u32>,
+}
+
+impl fmt::Display for CgroupInfo {
+    /// Multi-line `key: value` listing.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let or_na = |v: &Option<String>| v.clone().unwrap_or_else(|| "n/a".to_string());
+        let num_or_na = |v: Option<u64>| v.map_or_else(|| "n/a".to_string(), |v| v.to_string());
+        writeln!(f, "Cgroup:       {}", self.path)?;
+        writeln!(f, "Controllers:  {}", self.controllers.join(" "))?;
+        writeln!(f, "CPU max:      {}", or_na(&self.cpu_max))?;
+        match self.cpu_usage_usec {
+            Some(usec) => writeln!(f, "CPU usage:    {:.1} s", usec as f64 / 1_000_000.0)?,
+            None => writeln!(f, "CPU usage:    n/a")?,
+        }
+        let memory = self.memory_current.map_or_else(|| "n/a".to_string(), |m| format!("{} bytes", m));
+        writeln!(f, "Memory:       {}, limit {}", memory, or_na(&self.memory_max))?;
+        writeln!(f, "Pids:         {}, limit {}", num_or_na(self.pids_current), or_na(&self.pids_max))?;
+        if self.io_max.is_empty() {
+            writeln!(f, "IO max:       (none)")?;
+        } else {
+            writeln!(f, "IO max:       {}", self.io_max.join("; "))?;
+        }
+        let procs: Vec<String> = self.procs.iter().map(|p| p.to_string()).collect();
+        write!(f, "Processes:    {}", procs.join(" "))
+    }
+}
+
+/// Joins a user-supplied cgroup path to `root`, rejecting `..` so that
+/// nothing outside the hierarchy is touched.
+fn resolve(root: &Path, path: &str) -> Result<PathBuf, LpmError> {
+    let relative = Path::new(path.trim().trim_start_matches('/'));
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(LpmError::InvalidInput(format!("invalid cgroup path '{}'", path)));
+    }
+    Ok(root.join(relative))
+}
+
+/// Errors of cgroupfs operations that have no target process.
+fn cgroup_error(dir: &Path, err: io::Error) -> LpmError {
+    LpmError::Io(format!("{}: {}", dir.display(), err))
+}
+
+fn read_trimmed(dir: &Path, file: &str) -> Option<String> {
+    fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_string())
+}
+
+/// Writes `value` to an existing interface file; cgroupfs creates them all
+/// when the directory is made.
+fn write_file(dir: &Path, file: &str, value: &str) -> io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(dir.join(file))?
+        .write_all(value.as_bytes())
+}
+
+/// Enables all available controllers of `dir` for its children, ignoring
+/// the ones the kernel refuses.
+fn enable_controllers(dir: &Path) {
+    let available = read_trimmed(dir, "cgroup.controllers").unwrap_or_default();
+    let enabled = read_trimmed(dir, "cgroup.subtree_control").unwrap_or_default();
+    for controller in available.split_whitespace() {
+        if !enabled.split_whitespace().any(|c| c == controller) {
+            let _ = write_file(dir, "cgroup.subtree_control", &format!("+{}", controller));
+        }
+    }
+}
+
+impl ProcessManager {
+    /// The directory cgroup paths are resolved against.
+    pub fn cgroup_root(&self) -> &Path {
+        &self.cgroup_root
+    }
+
+    /// Uses `root` instead of the mounted hierarchy, e.g. a directory laid
+    /// out like cgroupfs.
+    pub fn set_cgroup_root(&mut self, root: impl Into<PathBuf>) {
+        self.cgroup_root = root.into();
+    }
+
+    /// The cgroup v2 path of `pid`, e.g. `/user.slice/user-1000.slice`.
+    pub fn cgroup_of(&mut self, pid: usize) -> Result<String, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        procfs::read_cgroup(raw)
+            .map_err(|e| LpmError::from_io(e, pid32))?
+            .ok_or_else(|| LpmError::InvalidInput(format!("process {} is not in a cgroup v2 hierarchy", pid)))
+    }
+
+    /// Usage and limits of the cgroup at `path` below the root.
+    pub fn cgroup_info(&mut self, path: &str) -> Result<CgroupInfo, LpmError> {
+        let dir = resolve(&self.cgroup_root, path)?;
+        if !dir.is_dir() {
+            return Err(LpmError::InvalidInput(format!("no cgroup at {}", dir.display())));
+        }
+        let procs = fs::read_to_string(dir.join("cgroup.procs")).map_err(|e| cgroup_error(&dir, e))?;
+        let number = |file| read_trimmed(&dir, file).and_then(|v| v.parse().ok());
+        Ok(CgroupInfo {
+            path: format!("/{}", path.trim().trim_matches('/')),
+            controllers: read_trimmed(&dir, "cgroup.controllers")
+                .map(|c| c.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            cpu_max: read_trimmed(&dir, "cpu.max"),
+            cpu_usage_usec: read_trimmed(&dir, "cpu.stat").and_then(|stat| {
+                stat.lines()
+                    .find_map(|l| l.strip_prefix("usage_usec "))
+                    .and_then(|v| v.trim().parse().ok())
+            }),
+            memory_current: number("memory.current"),
+            memory_max: read_trimmed(&dir, "memory.max"),
+            pids_current: number("pids.current"),
+            pids_max: read_trimmed(&dir, "pids.max"),
+            io_max: read_trimmed(&dir, "io.max")
+                .map(|io| io.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            procs: procs.lines().filter_map(|l| l.trim().parse().ok()).collect(),
+        })
+    }
+
+    /// Creates the cgroup at `path` and any missing parents. Before each
+    /// level is made, every controller its parent has is enabled for the
+    /// parent's children so that limits can be set. Enabling is best effort:
+    /// the kernel refuses it for a parent that still holds processes itself.
+    ///
+    /// Recorded in history under pid 0, as no process is involved.
+    pub fn create_cgroup(&mut self, path: &str) -> Result<(), LpmError> {
+        let result = self.make_cgroup(path);
+        self.record(ActionKind::CgroupCreate, 0, None, &[("cgroup", path.to_string())], &result);
+        result
+    }
+
+    fn make_cgroup(&self, path: &str) -> Result<(), LpmError> {
+        if !self.cgroup_root.is_dir() {
+            return Err(LpmError::InvalidInput(format!(
+                "no cgroup hierarchy at {}",
+                self.cgroup_root.display()
+            )));
+        }
+        let dir = resolve(&self.cgroup_root, path)?;
+        let mut missing: Vec<&Path> = dir.ancestors().take_while(|d| !d.exists()).collect();
+        missing.reverse();
+        for level in missing {
+            if let Some(parent) = level.parent() {
+                enable_controllers(parent);
+            }
+            fs::create_dir(level).map_err(|e| cgroup_error(level, e))?;
+        }
+        Ok(())
+    }
+
+    /// Moves `pid`, with all its threads, into the cgroup at `path`.
+    pub fn move_to_cgroup(&mut self, pid: usize, path: &str) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let old = self.cgroup_of(pid).ok();
+        let result = self.check_target(pid).and_then(|raw| {
+            let dir = resolve(&self.cgroup_root, path)?;
+            if !dir.is_dir() {
+                return Err(LpmError::InvalidInput(format!("no cgroup at {}", dir.display())));
+            }
+            write_file(&dir, "cgroup.procs", &raw.to_string()).map_err(|e| match e.raw_os_error() {
+                Some(libc::ESRCH) => LpmError::NoSuchProcess(pid as u32),
+                _ => cgroup_error(&dir, e),
+            })
+        });
+        let mut params = vec![("cgroup", path.to_string())];
+        if let Some(old) = old {
+            params.push(("old", old));
+        }
+        self.record(ActionKind::Cgroup, pid, name, &params, &result);
+        result
+    }
+
+    /// Sets `limit` of the cgroup at `path`; see [`CgroupLimit`] for the
+    /// accepted values. Fails if the controller is not enabled there.
+    ///
+    /// Recorded in history under pid 0 with the value as written and the
+    /// previous one.
+    pub fn set_cgroup_limit(&mut self, path: &str, limit: CgroupLimit, value: &str) -> Result<(), LpmError> {
+        let old = resolve(&self.cgroup_root, path).ok().and_then(|dir| read_trimmed(&dir, limit.file()));
+        let normalized = limit.normalize(value);
+        let result = normalized.clone().and_then(|value| {
+            let dir = resolve(&self.cgroup_root, path)?;
+            if !dir.is_dir() {
+                return Err(LpmError::InvalidInput(format!("no cgroup at {}", dir.display())));
+            }
+            if !dir.join(limit.file()).exists() {
+                return Err(LpmError::InvalidInput(format!(
+                    "the {} controller is not enabled for {}",
+                    limit,
+                    dir.display()
+                )));
+            }
+            write_file(&dir, limit.file(), &value).map_err(|e| cgroup_error(&dir, e))
+        });
+        let mut params = vec![
+            ("cgroup", path.to_string()),
+            ("limit", limit.file().to_string()),
+            ("value", normalized.unwrap_or_else(|_| value.to_string())),
+        ];
+        if let Some(old) = old {
+            params.push(("old", old));
+        }
+        self.record(ActionKind::CgroupLimit, 0, None, &params, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{History, Outcome};
+
+    /// A directory under the system temp dir laid out like cgroupfs,
+    /// removed again on drop.
+    struct FakeCgroupfs {
+        root: PathBuf,
+    }
+
+    impl FakeCgroupfs {
+        fn new(name: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("lpm-cgroup-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            let fake = FakeCgroupfs { root };
+            fake.write("", "cgroup.controllers", "cpu io memory pids\n");
+            fake.write("", "cgroup.subtree_control", "");
+            fake.write("", "cgroup.procs", "1\n");
+            fake
+        }
+
+        /// A child cgroup with every controller's interface files.
+        fn add_cgroup(&self, path: &str) {
+            fs::create_dir_all(self.root.join(path)).unwrap();
+            for (file, content) in [
+                ("cgroup.controllers", "cpu io memory pids\n"),
+                ("cgroup.subtree_control", ""),
+                ("cgroup.procs", "101\n102\n"),
+                ("cpu.max", "max 100000\n"),
+                ("cpu.stat", "usage_usec 123456\nuser_usec 100000\nsystem_usec 23456\n"),
+                ("memory.current", "4096\n"),
+                ("memory.max", "max\n"),
+                ("pids.current", "2\n"),
+                ("pids.max", "max\n"),
+                ("io.max", "8:0 rbps=max wbps=1048576 riops=max wiops=max\n"),
+            ] {
+                self.write(path, file, content);
+            }
+        }
+
+        fn write(&self, path: &str, file: &str, content: &str) {
+            fs::write(self.root.join(path).join(file), content).unwrap();
+        }
+
+        fn read(&self, path: &str, file: &str) -> String {
+            fs::read_to_string(self.root.join(path).join(file)).unwrap()
+        }
+
+        fn manager(&self) -> ProcessManager {
+            let mut manager = ProcessManager::with_history(History::in_memory());
+            manager.set_cgroup_root(&self.root);
+            manager
+        }
+    }
+
+    impl Drop for FakeCgroupfs {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn is_invalid<T: fmt::Debug>(result: Result<T, LpmError>) -> bool {
+        matches!(result, Err(LpmError::InvalidInput(_)))
+    }
+
+    #[test]
+    fn normalizes_cpu_limits() {
+        assert_eq!(CgroupLimit::Cpu.normalize("50%").unwrap(), "50000 100000");
+        assert_eq!(CgroupLimit::Cpu.normalize("250%").unwrap(), "250000 100000");
+        assert_eq!(CgroupLimit::Cpu.normalize("1%").unwrap(), "1000 100000");
+        assert_eq!(CgroupLimit::Cpu.normalize("max").unwrap(), "max");
+        assert_eq!(CgroupLimit::Cpu.normalize("20000 50000").unwrap(), "20000 50000");
+        assert!(is_invalid(CgroupLimit::Cpu.normalize("0.5%")));
+        assert!(is_invalid(CgroupLimit::Cpu.normalize("half%")));
+        assert!(is_invalid(CgroupLimit::Cpu.normalize("20000 fast")));
+        assert!(is_invalid(CgroupLimit::Cpu.normalize("1 2 3")));
+    }
+
+    #[test]
+    fn normalizes_memory_limits() {
+        assert_eq!(CgroupLimit::Memory.normalize("512M").unwrap(), "536870912");
+        assert_eq!(CgroupLimit::Memory.normalize("1g").unwrap(), "1073741824");
+        assert_eq!(CgroupLimit::Memory.normalize("4096").unwrap(), "4096");
+        assert_eq!(CgroupLimit::Memory.normalize(" max ").unwrap(), "max");
+        assert!(is_invalid(CgroupLimit::Memory.normalize("512X")));
+        assert!(is_invalid(CgroupLimit::Memory.normalize("M")));
+        assert!(is_invalid(CgroupLimit::Memory.normalize("99999999999T")));
+    }
+
+    #[test]
+    fn normalizes_pids_and_io_limits() {
+        assert_eq!(CgroupLimit::Pids.normalize("100").unwrap(), "100");
+        assert_eq!(CgroupLimit::Pids.normalize("max").unwrap(), "max");
+        assert!(is_invalid(CgroupLimit::Pids.normalize("-1")));
+
+        assert_eq!(CgroupLimit::Io.normalize("8:0 wbps=1048576").unwrap(), "8:0 wbps=1048576");
+        assert_eq!(CgroupLimit::Io.normalize("8:0  rbps=max   riops=100").unwrap(), "8:0 rbps=max riops=100");
+        assert!(is_invalid(CgroupLimit::Io.normalize("8:0")));
+        assert!(is_invalid(CgroupLimit::Io.normalize("sda wbps=1")));
+        assert!(is_invalid(CgroupLimit::Io.normalize("8:0 speed=1")));
+        assert!(is_invalid(CgroupLimit::Io.normalize("8:0 wbps")));
+    }
+
+    #[test]
+    fn resolve_stays_below_the_root() {
+        let root = Path::new("/sys/fs/cgroup");
+        assert_eq!(resolve(root, "/lpm/hogs").unwrap(), root.join("lpm/hogs"));
+        assert_eq!(resolve(root, "lpm").unwrap(), root.join("lpm"));
+        assert!(is_invalid(resolve(root, "../etc")));
+        assert!(is_invalid(resolve(root, "/lpm/../../etc")));
+        assert!(is_invalid(resolve(root, "./lpm")));
+    }
+
+    #[test]
+    fn reads_cgroup_info() {
+        let fake = FakeCgroupfs::new("info");
+        fake.add_cgroup("lpm/hogs");
+        let info = fake.manager().cgroup_info("/lpm/hogs/").unwrap();
+        assert_eq!(info.path, "/lpm/hogs");
+        assert_eq!(info.controllers, ["cpu", "io", "memory", "pids"]);
+        assert_eq!(info.cpu_max.as_deref(), Some("max 100000"));
+        assert_eq!(info.cpu_usage_usec, Some(123456));
+        assert_eq!(info.memory_current, Some(4096));
+        assert_eq!(info.memory_max.as_deref(), Some("max"));
+        assert_eq!(info.pids_current, Some(2));
+        assert_eq!(info.pids_max.as_deref(), Some("max"));
+        assert_eq!(info.io_max, ["8:0 rbps=max wbps=1048576 riops=max wiops=max"]);
+        assert_eq!(info.procs, [101, 102]);
+    }
+
+    #[test]
+    fn cgroup_info_without_controller_files() {
+        let fake = FakeCgroupfs::new("bare");
+        fs::create_dir(fake.root.join("bare")).unwrap();
+        fake.write("bare", "cgroup.procs", "");
+        let info = fake.manager().cgroup_info("bare").unwrap();
+        assert!(info.controllers.is_empty());
+        assert_eq!(info.cpu_max, None);
+        assert_eq!(info.memory_current, None);
+        assert!(info.io_max.is_empty());
+        assert!(info.procs.is_empty());
+
+        assert!(is_invalid(fake.manager().cgroup_info("missing")));
+    }
+
+    #[test]
+    fn create_cgroup_enables_controllers_of_each_parent() {
+        let fake = FakeCgroupfs::new("create");
+        fake.add_cgroup("lpm");
+        let mut manager = fake.manager();
+        manager.create_cgroup("/lpm/hogs/batch").unwrap();
+        assert!(fake.root.join("lpm/hogs/batch").is_dir());
+        // Controllers are enabled one write at a time, so the fake file
+        // only keeps the last one.
+        assert_eq!(fake.read("", "cgroup.subtree_control"), "");
+        assert_eq!(fake.read("lpm", "cgroup.subtree_control"), "+pids");
+
+        let entry = manager.history.entries().last().unwrap();
+        assert_eq!(entry.action, ActionKind::CgroupCreate);
+        assert_eq!(entry.pid, 0);
+        assert_eq!(entry.params["cgroup"], "/lpm/hogs/batch");
+        assert_eq!(entry.outcome, Outcome::Success);
+    }
+
+    #[test]
+    fn create_cgroup_skips_enabled_controllers() {
+        let fake = FakeCgroupfs::new("enabled");
+        fake.write("", "cgroup.subtree_control", "cpu io memory pids\n");
+        fake.manager().create_cgroup("lpm").unwrap();
+        assert!(fake.root.join("lpm").is_dir());
+        assert_eq!(fake.read("", "cgroup.subtree_control"), "cpu io memory pids\n");
+    }
+
+    #[test]
+    fn create_cgroup_rejects_escaping_paths() {
+        let fake = FakeCgroupfs::new("escape");
+        let mut manager = fake.manager();
+        assert!(is_invalid(manager.create_cgroup("../outside")));
+        assert!(!fake.root.with_file_name("outside").exists());
+        let entry = manager.history.entries().last().unwrap();
+        assert!(matches!(entry.outcome, Outcome::Failed { .. }));
+    }
+
+    #[test]
+    fn set_cgroup_limit_writes_the_normalized_value() {
+        let fake = FakeCgroupfs::new("limit");
+        fake.add_cgroup("lpm");
+        let mut manager = fake.manager();
+        manager.set_cgroup_limit("/lpm", CgroupLimit::Memory, "512M").unwrap();
+        manager.set_cgroup_limit("/lpm", CgroupLimit::Cpu, "50%").unwrap();
+        assert_eq!(fake.read("lpm", "memory.max"), "536870912");
+        assert_eq!(fake.read("lpm", "cpu.max"), "50000 100000");
+
+        let entry = manager.history.entries().last().unwrap();
+        assert_eq!(entry.action, ActionKind::CgroupLimit);
+        assert_eq!(entry.pid, 0);
+        assert_eq!(entry.params["cgroup"], "/lpm");
+        assert_eq!(entry.params["limit"], "cpu.max");
+        assert_eq!(entry.params["value"], "50000 100000");
+        assert_eq!(entry.params["old"], "max 100000");
+        assert_eq!(entry.outcome, Outcome::Success);
+    }
+
+    #[test]
+    fn set_cgroup_limit_needs_the_controller_file() {
+        let fake = FakeCgroupfs::new("nofile");
+        fake.add_cgroup("lpm");
+        fs::remove_file(fake.root.join("lpm/pids.max")).unwrap();
+        let mut manager = fake.manager();
+        assert!(is_invalid(manager.set_cgroup_limit("lpm", CgroupLimit::Pids, "100")));
+        assert!(!fake.root.join("lpm/pids.max").exists());
+        assert!(is_invalid(manager.set_cgroup_limit("lpm", CgroupLimit::Memory, "lots")));
+        assert_eq!(fake.read("lpm", "memory.max"), "max\n");
+        assert!(is_invalid(manager.set_cgroup_limit("missing", CgroupLimit::Memory, "1G")));
+
+        let failed = manager
+            .history
+            .entries()
+            .iter()
+            .filter(|e| matches!(e.outcome, Outcome::Failed { .. }))
+            .count();
+        assert_eq!(failed, 3);
+    }
+}
@@ -0,0 +1,76 @@
+// src/error.rs for lpm-core
+
+use std::fmt;
+use std::io;
+
+/// Why a process action failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LpmError {
+    /// The target process does not exist (ESRCH).
+    NoSuchProcess(u32),
+    /// The caller may not act on the target process (EPERM/EACCES).
+    PermissionDenied(u32),
+    /// Nice values must lie in `-20..=19`.
+    InvalidNice(i32),
+    /// Unknown signal name or number.
+    InvalidSignal(String),
+    /// lpm refuses to act on init, kernel threads and itself.
+    ProtectedProcess(u32),
+    /// The process survived SIGKILL, e.g. because it is stuck in the kernel.
+    StillRunning(u32),
+    /// A replacement process could not be started.
+    SpawnFailed(String),
+    /// A user-supplied argument such as an export format could not be used.
+    InvalidInput(String),
+    /// Any other OS or I/O failure.
+    Io(String),
+}
+
+impl LpmError {
+    /// Maps the current `errno` after a failed syscall on `pid`.
+    pub(crate) fn last_os_error(pid: u32) -> Self {
+        Self::from_io(io::Error::last_os_error(), pid)
+    }
+
+    pub(crate) fn from_io(err: io::Error, pid: u32) -> Self {
+        match err.raw_os_error() {
+            // ENOENT comes from /proc/<pid>/* files of a process that is gone.
+            Some(libc::ESRCH) | Some(libc::ENOENT) => LpmError::NoSuchProcess(pid),
+            Some(libc::EPERM) | Some(libc::EACCES) => LpmError::PermissionDenied(pid),
+            _ => LpmError::Io(err.to_string()),
+        }
+    }
+}
+
+/// Turns a `0`/`-1` syscall return value into a `Result`.
+pub(crate) fn check_os(ret: libc::c_int, pid: u32) -> Result<(), LpmError> {
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(LpmError::last_os_error(pid))
+    }
+}
+
+impl fmt::Display for LpmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LpmError::NoSuchProcess(pid) => write!(f, "no such process: {}", pid),
+            LpmError::PermissionDenied(pid) => write!(f, "permission denied for process {}", pid),
+            LpmError::InvalidNice(nice) => write!(f, "invalid nice value {} (expected -20..=19)", nice),
+            LpmError::InvalidSignal(sig) => write!(f, "invalid signal '{}'", sig),
+            LpmError::ProtectedProcess(pid) => write!(f, "process {} is protected", pid),
+            LpmError::StillRunning(pid) => write!(f, "process {} is still running after SIGKILL", pid),
+            LpmError::SpawnFailed(msg) => write!(f, "failed to spawn process: {}", msg),
+            LpmError::InvalidInput(msg) => f.write_str(msg),
+            LpmError::Io(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for LpmError {}
+
+impl From<io::Error> for LpmError {
+    fn from(err: io::Error) -> Self {
+        LpmError::Io(err.to_string())
+    }
+}
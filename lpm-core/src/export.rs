@@ -0,0 +1,415 @@
+// src/export.rs for lpm-core
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{LpmError, ProcessManager, ProcessSnapshot};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Pretty-printed JSON array.
+    Json,
+    /// CSV with a header row.
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+    /// Markdown table.
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Guesses the format from a file extension such as `.csv` or `.jsonl`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = LpmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            _ => Err(LpmError::InvalidInput(format!(
+                "unknown export format '{}' (expected json, csv, ndjson or markdown)",
+                s
+            ))),
+        }
+    }
+}
+
+/// A field of [`ProcessSnapshot`] that can be selected for export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Pid,
+    Ppid,
+    Name,
+    Cmdline,
+    Exe,
+    Uid,
+    User,
+    Euid,
+    EffectiveUser,
+    Gid,
+    Group,
+    Egid,
+    /// Supplementary gids.
+    Groups,
+    State,
+    Cpu,
+    Memory,
+    VirtualMemory,
+    StartTime,
+    Threads,
+    ReadBytes,
+    WrittenBytes,
+    TotalReadBytes,
+    TotalWrittenBytes,
+    Policy,
+    RtPriority,
+    IoPriority,
+    Cgroup,
+    OomScore,
+    OomScoreAdj,
+}
+
+impl Column {
+    pub const ALL: &'static [Column] = &[
+        Column::Pid,
+        Column::Ppid,
+        Column::Name,
+        Column::Cmdline,
+        Column::Exe,
+        Column::Uid,
+        Column::User,
+        Column::Euid,
+        Column::EffectiveUser,
+        Column::Gid,
+        Column::Group,
+        Column::Egid,
+        Column::Groups,
+        Column::State,
+        Column::Cpu,
+        Column::Memory,
+        Column::VirtualMemory,
+        Column::StartTime,
+        Column::Threads,
+        Column::ReadBytes,
+        Column::WrittenBytes,
+        Column::TotalReadBytes,
+        Column::TotalWrittenBytes,
+        Column::Policy,
+        Column::RtPriority,
+        Column::IoPriority,
+        Column::Cgroup,
+        Column::OomScore,
+        Column::OomScoreAdj,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Pid => "pid",
+            Column::Ppid => "ppid",
+            Column::Name => "name",
+            Column::Cmdline => "cmdline",
+            Column::Exe => "exe",
+            Column::Uid => "uid",
+            Column::User => "user",
+            Column::Euid => "euid",
+            Column::EffectiveUser => "effective_user",
+            Column::Gid => "gid",
+            Column::Group => "group",
+            Column::Egid => "egid",
+            Column::Groups => "groups",
+            Column::State => "state",
+            Column::Cpu => "cpu",
+            Column::Memory => "memory",
+            Column::VirtualMemory => "virtual_memory",
+            Column::StartTime => "start_time",
+            Column::Threads => "threads",
+            Column::ReadBytes => "read_bytes",
+            Column::WrittenBytes => "written_bytes",
+            Column::TotalReadBytes => "total_read_bytes",
+            Column::TotalWrittenBytes => "total_written_bytes",
+            Column::Policy => "policy",
+            Column::RtPriority => "rt_priority",
+            Column::IoPriority => "io_priority",
+            Column::Cgroup => "cgroup",
+            Column::OomScore => "oom_score",
+            Column::OomScoreAdj => "oom_score_adj",
+        }
+    }
+
+    /// Parses a comma-separated column list such as `pid,name,cpu`.
+    pub fn parse_list(s: &str) -> Result<Vec<Column>, LpmError> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    fn json(&self, p: &ProcessSnapshot) -> Value {
+        match self {
+            Column::Pid => p.pid.into(),
+            Column::Ppid => p.ppid.into(),
+            Column::Name => p.name.clone().into(),
+            Column::Cmdline => p.cmdline.clone().into(),
+            Column::Exe => p.exe.to_string_lossy().into_owned().into(),
+            Column::Uid => p.uid.into(),
+            Column::User => p.user.clone().into(),
+            Column::Euid => p.euid.into(),
+            Column::EffectiveUser => p.effective_user.clone().into(),
+            Column::Gid => p.gid.into(),
+            Column::Group => p.group.clone().into(),
+            Column::Egid => p.egid.into(),
+            Column::Groups => p.groups.clone().into(),
+            Column::State => p.state.to_string().into(),
+            Column::Cpu => p.cpu_usage.into(),
+            Column::Memory => p.memory.into(),
+            Column::VirtualMemory => p.virtual_memory.into(),
+            Column::StartTime => p.start_time.into(),
+            Column::Threads => p.threads.into(),
+            Column::ReadBytes => p.disk_io.read_bytes.into(),
+            Column::WrittenBytes => p.disk_io.written_bytes.into(),
+            Column::TotalReadBytes => p.disk_io.total_read_bytes.into(),
+            Column::TotalWrittenBytes => p.disk_io.total_written_bytes.into(),
+            Column::Policy => p.scheduling.policy.as_str().into(),
+            Column::RtPriority => p.scheduling.priority.into(),
+            Column::IoPriority => p.io_priority.to_string().into(),
+            Column::Cgroup => p.cgroup.clone().into(),
+            Column::OomScore => p.oom_score.into(),
+            Column::OomScoreAdj => p.oom_score_adj.into(),
+        }
+    }
+
+    /// The value as a CSV or Markdown cell; lists are joined.
+    fn text(&self, p: &ProcessSnapshot) -> String {
+        match self {
+            Column::Cmdline => p.cmdline.join(" "),
+            Column::Groups => p.groups.iter().map(u32::to_string).collect::<Vec<_>>().join(","),
+            _ => match self.json(p) {
+                Value::Null => String::new(),
+                Value::String(s) => s,
+                other => other.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Column {
+    type Err = LpmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        Column::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name() == s)
+            .ok_or_else(|| LpmError::InvalidInput(format!("unknown column '{}'", s)))
+    }
+}
+
+/// Writes `processes` to `out`.
+///
+/// Without `columns`, JSON and NDJSON contain the complete snapshots (which
+/// can be read back as [`ProcessSnapshot`]) and CSV/Markdown every column.
+pub fn write_processes<W: Write>(
+    out: W,
+    processes: &[ProcessSnapshot],
+    format: ExportFormat,
+    columns: Option<&[Column]>,
+) -> Result<(), LpmError> {
+    let mut out = BufWriter::new(out);
+    let json_err = |e: serde_json::Error| LpmError::Io(e.to_string());
+    let rows: Vec<Row> = processes.iter().map(|p| Row { process: p, columns }).collect();
+    let cols = columns.unwrap_or(Column::ALL);
+
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &rows).map_err(json_err)?;
+            writeln!(out)?;
+        }
+        ExportFormat::Ndjson => {
+            for row in &rows {
+                serde_json::to_writer(&mut out, row).map_err(json_err)?;
+                writeln!(out)?;
+            }
+        }
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(&mut out);
+            let csv_err = |e: csv::Error| LpmError::Io(e.to_string());
+            csv.write_record(cols.iter().map(|c| c.name())).map_err(csv_err)?;
+            for p in processes {
+                csv.write_record(cols.iter().map(|c| c.text(p))).map_err(csv_err)?;
+            }
+            csv.flush()?;
+        }
+        ExportFormat::Markdown => {
+            let cells = |values: Vec<String>| format!("| {} |", values.join(" | "));
+            writeln!(out, "{}", cells(cols.iter().map(|c| c.name().to_string()).collect()))?;
+            writeln!(out, "{}", cells(cols.iter().map(|_| "---".to_string()).collect()))?;
+            for p in processes {
+                let values = cols.iter().map(|c| escape_markdown(&c.text(p))).collect();
+                writeln!(out, "{}", cells(values))?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// A snapshot restricted to the selected columns, serialized in column order.
+struct Row<'a> {
+    process: &'a ProcessSnapshot,
+    columns: Option<&'a [Column]>,
+}
+
+impl Serialize for Row<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(columns) = self.columns else {
+            return self.process.serialize(serializer);
+        };
+        let mut map = serializer.serialize_map(Some(columns.len()))?;
+        for column in columns {
+            map.serialize_entry(column.name(), &column.json(self.process))?;
+        }
+        map.end()
+    }
+}
+
+fn escape_markdown(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+impl ProcessManager {
+    /// Exports the current process list to `path` and returns how many
+    /// processes were written. The format defaults to the file extension.
+    pub fn export_processes(
+        &mut self,
+        path: impl AsRef<Path>,
+        format: Option<ExportFormat>,
+        columns: Option<&[Column]>,
+    ) -> Result<usize, LpmError> {
+        let path = path.as_ref();
+        let format = format.or_else(|| ExportFormat::from_path(path)).ok_or_else(|| {
+            LpmError::InvalidInput(format!(
+                "cannot tell the export format of '{}'; pass one explicitly",
+                path.display()
+            ))
+        })?;
+        let mut processes = self.list_processes();
+        processes.sort_by_key(|p| p.pid);
+        write_processes(File::create(path)?, &processes, format, columns)?;
+        Ok(processes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn processes() -> Vec<ProcessSnapshot> {
+        let mut first = ProcessSnapshot::fixture(1, "init");
+        first.cpu_usage = 1.5;
+        let mut second = ProcessSnapshot::fixture(42, "worker");
+        second.cmdline = vec!["worker".to_string(), "--tags=a,b".to_string(), "say \"hi\"".to_string()];
+        second.groups = vec![27, 100];
+        second.user = None;
+        vec![first, second]
+    }
+
+    fn export(format: ExportFormat, columns: Option<&[Column]>) -> String {
+        let mut out = Vec::new();
+        write_processes(&mut out, &processes(), format, columns).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_has_a_header_and_quotes_fields() {
+        let columns = [Column::Pid, Column::Name, Column::User, Column::Cmdline, Column::Groups];
+        let csv = export(ExportFormat::Csv, Some(&columns));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "pid,name,user,cmdline,groups");
+        assert_eq!(lines[1], "1,init,alice,/usr/bin/init,");
+        assert_eq!(lines[2], r#"42,worker,,"worker --tags=a,b say ""hi""","27,100""#);
+        assert_eq!(lines.len(), 3);
+
+        let all = export(ExportFormat::Csv, None);
+        let header: Vec<&str> = all.lines().next().unwrap().split(',').collect();
+        assert_eq!(header, Column::ALL.iter().map(|c| c.name()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn markdown_escapes_cells() {
+        assert_eq!(escape_markdown(r"a|b\c"), r"a\|b\\c");
+        assert_eq!(escape_markdown("one\ntwo\r\nthree"), "one two  three");
+
+        let mut list = processes();
+        list[1].name = "a|b\nc".to_string();
+        let mut out = Vec::new();
+        write_processes(&mut out, &list, ExportFormat::Markdown, Some(&[Column::Pid, Column::Name])).unwrap();
+        let md = String::from_utf8(out).unwrap();
+        assert_eq!(md, "| pid | name |\n| --- | --- |\n| 1 | init |\n| 42 | a\\|b c |\n");
+    }
+
+    #[test]
+    fn columns_select_json_keys_in_order() {
+        let columns = [Column::Name, Column::Pid, Column::Groups, Column::Cpu];
+        let json = export(ExportFormat::Json, Some(&columns));
+        // serde_json maps sort their keys, so check the order in the text.
+        let first = &json[..json.find('}').unwrap()];
+        let positions: Vec<usize> = ["\"name\"", "\"pid\"", "\"groups\"", "\"cpu\""]
+            .iter()
+            .map(|key| first.find(key).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", json);
+        let rows: Vec<serde_json::Map<String, Value>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows[0].len(), 4);
+        assert_eq!(rows[1]["groups"], serde_json::json!([27, 100]));
+
+        let ndjson = export(ExportFormat::Ndjson, Some(&[Column::Pid, Column::User]));
+        assert_eq!(ndjson, "{\"pid\":1,\"user\":\"alice\"}\n{\"pid\":42,\"user\":null}\n");
+    }
+
+    #[test]
+    fn full_json_reads_back() {
+        let back: Vec<ProcessSnapshot> = serde_json::from_str(&export(ExportFormat::Json, None)).unwrap();
+        assert_eq!(back, processes());
+        let lines: Vec<ProcessSnapshot> = export(ExportFormat::Ndjson, None)
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines, processes());
+    }
+
+    #[test]
+    fn formats_and_columns_parse() {
+        assert_eq!(ExportFormat::from_path(Path::new("out.jsonl")), Some(ExportFormat::Ndjson));
+        assert_eq!(ExportFormat::from_path(Path::new("out.md")), Some(ExportFormat::Markdown));
+        assert_eq!(ExportFormat::from_path(Path::new("out.CSV")), Some(ExportFormat::Csv));
+        assert_eq!(ExportFormat::from_path(Path::new("out.json")), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::from_path(Path::new("out.xlsx")), None);
+        assert_eq!(ExportFormat::from_path(Path::new("out")), None);
+
+        assert_eq!(Column::parse_list("pid, NAME,,cpu").unwrap(), [Column::Pid, Column::Name, Column::Cpu]);
+        assert!(Column::parse_list("pid,colour").is_err());
+        for &column in Column::ALL {
+            assert_eq!(column.name().parse::<Column>().unwrap(), column);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+// src/fds.rs for lpm-core
+//
+// Open file descriptors from /proc/<pid>/fd, like a per-process lsof.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::{procfs, LpmError, ProcessManager};
+
+/// What an open file descriptor refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FdKind {
+    Regular,
+    Directory,
+    /// A file that has been unlinked but is still open, so its disk space
+    /// is not freed until the descriptor is closed.
+    Deleted,
+    Socket,
+    Pipe,
+    /// eventfd, epoll, inotify, timerfd and similar kernel objects.
+    AnonInode,
+    /// Character or block device.
+    Device,
+    /// Anything else, e.g. namespace handles.
+    Other,
+}
+
+impl FdKind {
+    pub const ALL: &'static [FdKind] = &[
+        FdKind::Regular,
+        FdKind::Directory,
+        FdKind::Deleted,
+        FdKind::Socket,
+        FdKind::Pipe,
+        FdKind::AnonInode,
+        FdKind::Device,
+        FdKind::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FdKind::Regular => "regular",
+            FdKind::Directory => "directory",
+            FdKind::Deleted => "deleted",
+            FdKind::Socket => "socket",
+            FdKind::Pipe => "pipe",
+            FdKind::AnonInode => "anon_inode",
+            FdKind::Device => "device",
+            FdKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for FdKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FdKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        FdKind::ALL
+            .iter()
+            .copied()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| format!("unknown file kind '{}'", s))
+    }
+}
+
+/// One entry of `/proc/<pid>/fd`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenFile {
+    pub fd: u32,
+    pub kind: FdKind,
+    /// The link target as the kernel shows it, e.g. `/var/log/app.log`,
+    /// `socket:[48213]` or `anon_inode:[eventfd]`. Deleted files keep their
+    /// last path without the ` (deleted)` marker.
+    pub target: String,
+    /// Size in bytes of regular and deleted files.
+    pub size: Option<u64>,
+    /// Inode number; for sockets and pipes the one in the brackets.
+    pub inode: Option<u64>,
+}
+
+impl OpenFile {
+    fn read(pid: libc::pid_t, fd: u32) -> Option<Self> {
+        let link = format!("/proc/{}/fd/{}", pid, fd);
+        let target = fs::read_link(&link).ok()?.to_string_lossy().into_owned();
+        // Following the link reaches the open file even if it was unlinked.
+        let meta = fs::metadata(&link).ok();
+        let bracketed = |prefix: &str| {
+            target.strip_prefix(prefix)?.strip_prefix('[')?.strip_suffix(']')?.parse().ok()
+        };
+
+        let (kind, target, inode) = if let Some(inode) = bracketed("socket:") {
+            (FdKind::Socket, target, Some(inode))
+        } else if let Some(inode) = bracketed("pipe:") {
+            (FdKind::Pipe, target, Some(inode))
+        } else if target.starts_with("anon_inode:") {
+            (FdKind::AnonInode, target, None)
+        } else if let Some(path) = target.strip_suffix(" (deleted)").filter(|t| t.starts_with('/')) {
+            (FdKind::Deleted, path.to_string(), meta.as_ref().map(|m| m.ino()))
+        } else if !target.starts_with('/') {
+            (FdKind::Other, target, None)
+        } else {
+            let kind = match meta.as_ref().map(|m| m.file_type()) {
+                Some(t) if t.is_char_device() || t.is_block_device() => FdKind::Device,
+                Some(t) if t.is_dir() => FdKind::Directory,
+                Some(t) if t.is_fifo() => FdKind::Pipe,
+                Some(t) if t.is_socket() => FdKind::Socket,
+                _ => FdKind::Regular,
+            };
+            (kind, target, meta.as_ref().map(|m| m.ino()))
+        };
+        let size = match kind {
+            FdKind::Regular | FdKind::Deleted => meta.map(|m| m.len()),
+            _ => None,
+        };
+        Some(OpenFile { fd, kind, target, size, inode })
+    }
+}
+
+/// The descriptor table of a process together with its RLIMIT_NOFILE.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenFiles {
+    pub pid: u32,
+    pub files: Vec<OpenFile>,
+    /// Soft and hard limit on open files; `None` means unlimited.
+    pub soft_limit: Option<u64>,
+    pub hard_limit: Option<u64>,
+}
+
+impl OpenFiles {
+    pub fn count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Open descriptors as a percentage of the soft limit.
+    pub fn usage_percent(&self) -> Option<f64> {
+        self.soft_limit
+            .filter(|&limit| limit > 0)
+            .map(|limit| self.files.len() as f64 / limit as f64 * 100.0)
+    }
+
+    /// Bytes held by files that are deleted but still open.
+    pub fn deleted_bytes(&self) -> u64 {
+        self.files
+            .iter()
+            .filter(|f| f.kind == FdKind::Deleted)
+            .filter_map(|f| f.size)
+            .sum()
+    }
+}
+
+impl fmt::Display for OpenFiles {
+    /// A summary line such as `42 open of 1024 (4.1%)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} open", self.count())?;
+        match (self.soft_limit, self.usage_percent()) {
+            (Some(limit), Some(pct)) => write!(f, " of {} ({:.1}%)", limit, pct)?,
+            _ => f.write_str(" (no limit)")?,
+        }
+        let deleted = self.deleted_bytes();
+        if deleted > 0 {
+            write!(f, ", {} bytes in deleted files", deleted)?;
+        }
+        Ok(())
+    }
+}
+
+/// A process holding a file open, from [`ProcessManager::holders_of`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileHolder {
+    pub pid: u32,
+    pub name: String,
+    pub fd: u32,
+}
+
+pub(crate) fn fd_numbers(pid: libc::pid_t) -> std::io::Result<Vec<u32>> {
+    let mut fds: Vec<u32> = fs::read_dir(format!("/proc/{}/fd", pid))?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    fds.sort_unstable();
+    Ok(fds)
+}
+
+impl ProcessManager {
+    /// Lists the open file descriptors of `pid`. Reading another user's
+    /// descriptors needs the same privileges as ptrace.
+    pub fn open_files(&mut self, pid: usize) -> Result<OpenFiles, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        let io_err = |e| LpmError::from_io(e, pid32);
+        let fds = fd_numbers(raw).map_err(io_err)?;
+        let (soft_limit, hard_limit) = procfs::read_limit(raw, "Max open files").map_err(io_err)?;
+        Ok(OpenFiles {
+            pid: pid32,
+            // Descriptors closed while the directory is read are skipped.
+            files: fds.into_iter().filter_map(|fd| OpenFile::read(raw, fd)).collect(),
+            soft_limit,
+            hard_limit,
+        })
+    }
+
+    /// Finds the processes that have `path` open. An existing path is matched
+    /// by device and inode, so hard links and other mount views of it count;
+    /// a path that no longer exists is matched against deleted files by name.
+    /// Processes whose descriptors cannot be read are skipped.
+    pub fn holders_of(&mut self, path: &Path) -> Vec<FileHolder> {
+        let wanted = fs::metadata(path).ok().map(|m| (m.dev(), m.ino()));
+        let wanted_name = path.to_string_lossy();
+        let mut holders = Vec::new();
+        for p in self.list_processes() {
+            let Ok(fds) = fd_numbers(p.pid as libc::pid_t) else {
+                continue;
+            };
+            for fd in fds {
+                let link = format!("/proc/{}/fd/{}", p.pid, fd);
+                let held = match wanted {
+                    Some(id) => fs::metadata(&link).is_ok_and(|m| (m.dev(), m.ino()) == id),
+                    None => fs::read_link(&link).is_ok_and(|target| {
+                        target.to_string_lossy().strip_suffix(" (deleted)") == Some(&*wanted_name)
+                    }),
+                };
+                if held {
+                    holders.push(FileHolder { pid: p.pid, name: p.name.clone(), fd });
+                }
+            }
+        }
+        holders.sort_by_key(|h| (h.pid, h.fd));
+        holders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::History;
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+
+    fn find(manager: &mut ProcessManager, fd: u32) -> OpenFile {
+        let files = manager.open_files(std::process::id() as usize).unwrap();
+        files.files.into_iter().find(|f| f.fd == fd).unwrap()
+    }
+
+    #[test]
+    fn follows_files_through_deletion() {
+        let dir = fs::canonicalize(std::env::temp_dir()).unwrap();
+        let path = dir.join(format!("lpm-fds-{}.log", std::process::id()));
+        let mut file = fs::File::create(&path).unwrap();
+        file.write_all(&[b'x'; 4096]).unwrap();
+        let fd = file.as_raw_fd() as u32;
+        let own = std::process::id();
+        let mut manager = ProcessManager::with_history(History::in_memory());
+
+        let open = find(&mut manager, fd);
+        assert_eq!(open.kind, FdKind::Regular);
+        assert_eq!(open.target, path.to_string_lossy());
+        assert_eq!(open.size, Some(4096));
+        assert_eq!(open.inode, Some(fs::metadata(&path).unwrap().ino()));
+        let holders = manager.holders_of(&path);
+        assert!(holders.iter().any(|h| h.pid == own && h.fd == fd), "{:?}", holders);
+
+        fs::remove_file(&path).unwrap();
+        let deleted = find(&mut manager, fd);
+        assert_eq!(deleted.kind, FdKind::Deleted);
+        assert_eq!(deleted.target, path.to_string_lossy());
+        assert_eq!(deleted.size, Some(4096));
+        let files = manager.open_files(own as usize).unwrap();
+        assert!(files.deleted_bytes() >= 4096);
+        let holders = manager.holders_of(&path);
+        assert!(holders.iter().any(|h| h.pid == own && h.fd == fd), "{:?}", holders);
+
+        drop(file);
+        assert!(!manager.holders_of(&path).iter().any(|h| h.pid == own && h.fd == fd));
+    }
+
+    #[test]
+    fn classifies_pipes_and_directories() {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        let dir = fs::File::open("/").unwrap();
+        let mut manager = ProcessManager::with_history(History::in_memory());
+
+        let pipe = find(&mut manager, fds[0] as u32);
+        assert_eq!(pipe.kind, FdKind::Pipe);
+        assert!(pipe.target.starts_with("pipe:["));
+        assert!(pipe.inode.is_some());
+        assert_eq!(pipe.size, None);
+        assert_eq!(find(&mut manager, dir.as_raw_fd() as u32).kind, FdKind::Directory);
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+    }
+
+    #[test]
+    fn usage_counts_against_the_soft_limit() {
+        let files = OpenFiles {
+            pid: 1,
+            files: vec![
+                OpenFile { fd: 0, kind: FdKind::Device, target: "/dev/null".to_string(), size: None, inode: None },
+                OpenFile { fd: 3, kind: FdKind::Deleted, target: "/tmp/a".to_string(), size: Some(100), inode: None },
+                OpenFile { fd: 4, kind: FdKind::Deleted, target: "/tmp/b".to_string(), size: Some(28), inode: None },
+            ],
+            soft_limit: Some(12),
+            hard_limit: None,
+        };
+        assert_eq!(files.count(), 3);
+        assert_eq!(files.usage_percent(), Some(25.0));
+        assert_eq!(files.deleted_bytes(), 128);
+        assert_eq!(files.to_string(), "3 open of 12 (25.0%), 128 bytes in deleted files");
+    }
+}
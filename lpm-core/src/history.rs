@@ -0,0 +1,382 @@
+// src/history.rs for lpm-core
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::LpmError;
+
+/// One action performed through lpm.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Local>,
+    pub action: ActionKind,
+    pub pid: u32,
+    pub name: Option<String>,
+    /// Action parameters such as the nice value, in display form.
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    pub outcome: Outcome,
+    /// Real uid of the user who ran lpm.
+    pub uid: u32,
+    /// The alert rule that ran this action automatically; `None` for actions
+    /// requested by a user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionKind {
+    Kill,
+    Terminate,
+    Signal,
+    Suspend,
+    Resume,
+    Renice,
+    Restart,
+    /// CPU affinity change.
+    Affinity,
+    /// Scheduling policy or real-time priority change.
+    Schedule,
+    /// I/O class or level change.
+    IoNice,
+    /// Resource limit change.
+    Limit,
+    /// Move into another cgroup.
+    Cgroup,
+    /// cgroup creation; not tied to a process.
+    CgroupCreate,
+    /// cgroup limit change; not tied to a process.
+    CgroupLimit,
+    /// OOM score adjustment change.
+    OomAdjust,
+    /// A remediation hook command run for an alert.
+    Hook,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Outcome {
+    Success,
+    Failed { reason: String },
+}
+
+impl<T> From<&Result<T, LpmError>> for Outcome {
+    fn from(result: &Result<T, LpmError>) -> Self {
+        match result {
+            Ok(_) => Outcome::Success,
+            Err(e) => Outcome::Failed { reason: e.to_string() },
+        }
+    }
+}
+
+impl ActionKind {
+    pub const ALL: &'static [ActionKind] = &[
+        ActionKind::Kill,
+        ActionKind::Terminate,
+        ActionKind::Signal,
+        ActionKind::Suspend,
+        ActionKind::Resume,
+        ActionKind::Renice,
+        ActionKind::Restart,
+        ActionKind::Affinity,
+        ActionKind::Schedule,
+        ActionKind::IoNice,
+        ActionKind::Limit,
+        ActionKind::Cgroup,
+        ActionKind::CgroupCreate,
+        ActionKind::CgroupLimit,
+        ActionKind::OomAdjust,
+        ActionKind::Hook,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActionKind::Kill => "kill",
+            ActionKind::Terminate => "terminate",
+            ActionKind::Signal => "signal",
+            ActionKind::Suspend => "suspend",
+            ActionKind::Resume => "resume",
+            ActionKind::Renice => "renice",
+            ActionKind::Restart => "restart",
+            ActionKind::Affinity => "affinity",
+            ActionKind::Schedule => "sched",
+            ActionKind::IoNice => "ionice",
+            ActionKind::Limit => "limit",
+            ActionKind::Cgroup => "cgroup",
+            ActionKind::CgroupCreate => "cgroup_create",
+            ActionKind::CgroupLimit => "cgroup_limit",
+            ActionKind::OomAdjust => "oom_adj",
+            ActionKind::Hook => "hook",
+        }
+    }
+}
+
+impl fmt::Display for ActionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ActionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        ActionKind::ALL
+            .iter()
+            .copied()
+            .find(|a| a.as_str() == s)
+            .ok_or_else(|| format!("unknown action '{}'", s))
+    }
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} uid={} {} PID {}",
+            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            self.uid,
+            self.action.as_str().to_uppercase(),
+            self.pid
+        )?;
+        if let Some(name) = &self.name {
+            write!(f, " ({})", name)?;
+        }
+        for (key, value) in &self.params {
+            write!(f, " {}={}", key, value)?;
+        }
+        if let Some(rule) = &self.trigger {
+            write!(f, " [auto: alert {}]", rule)?;
+        }
+        match &self.outcome {
+            Outcome::Success => write!(f, " -> ok"),
+            Outcome::Failed { reason } => write!(f, " -> failed: {}", reason),
+        }
+    }
+}
+
+/// Filter for [`History::query`]; unset fields match everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryQuery {
+    pub since: Option<DateTime<Local>>,
+    pub until: Option<DateTime<Local>>,
+    pub action: Option<ActionKind>,
+    pub pid: Option<u32>,
+}
+
+impl HistoryQuery {
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.since.is_none_or(|t| entry.timestamp >= t)
+            && self.until.is_none_or(|t| entry.timestamp <= t)
+            && self.action.is_none_or(|a| entry.action == a)
+            && self.pid.is_none_or(|p| entry.pid == p)
+    }
+}
+
+/// Action history, mirrored to an append-only JSON Lines file.
+#[derive(Debug, Default)]
+pub struct History {
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// History that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Loads the history stored at `path`, skipping lines that do not parse,
+    /// including ones that are not valid UTF-8. A missing file yields an
+    /// empty history that will be created on the first record.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let entries = match fs::File::open(&path) {
+            Ok(file) => BufReader::new(file)
+                .split(b'\n')
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_slice(&line).ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        Self { path: Some(path), entries }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn query(&self, query: &HistoryQuery) -> Vec<&HistoryEntry> {
+        self.entries.iter().filter(|e| query.matches(e)).collect()
+    }
+
+    /// Keeps `entry` in memory and appends it to the backing file.
+    ///
+    /// The entry is kept even if writing fails, so the current session still
+    /// sees it.
+    pub fn record(&mut self, entry: HistoryEntry) -> io::Result<()> {
+        let result = match &self.path {
+            Some(path) => append_line(path, &entry),
+            None => Ok(()),
+        };
+        self.entries.push(entry);
+        result
+    }
+}
+
+fn append_line(path: &Path, entry: &HistoryEntry) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())
+}
+
+/// `$XDG_STATE_HOME/lpm/history.jsonl`, falling back to `~/.local/state`.
+pub fn default_history_path() -> Option<PathBuf> {
+    let state_dir = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+    Some(state_dir.join("lpm").join("history.jsonl"))
+}
+
+/// Parses a local time given as RFC 3339, `YYYY-MM-DD HH:MM[:SS]` or
+/// `YYYY-MM-DD` (midnight).
+pub fn parse_time(s: &str) -> Option<DateTime<Local>> {
+    let s = s.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Some(t.with_timezone(&Local));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Local> {
+        parse_time(s).unwrap()
+    }
+
+    fn entry(timestamp: &str, action: ActionKind, pid: u32, outcome: Outcome) -> HistoryEntry {
+        HistoryEntry {
+            timestamp: at(timestamp),
+            action,
+            pid,
+            name: Some("sleep".to_string()),
+            params: BTreeMap::from([("signal".to_string(), "TERM".to_string())]),
+            outcome,
+            uid: 1000,
+            trigger: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_the_file_and_skips_garbage() {
+        let dir = std::env::temp_dir().join(format!("lpm-history-{}", std::process::id()));
+        let path = dir.join("nested/history.jsonl");
+        let _ = fs::remove_dir_all(&dir);
+
+        let first = entry("2026-03-01 10:00:00", ActionKind::Kill, 42, Outcome::Success);
+        let second = HistoryEntry {
+            trigger: Some("high-cpu".to_string()),
+            ..entry("2026-03-01 10:05:00", ActionKind::Renice, 43, Outcome::Failed { reason: "denied".to_string() })
+        };
+        let mut history = History::load(&path);
+        assert!(history.entries().is_empty());
+        history.record(first.clone()).unwrap();
+        // A torn write and a line that is not even UTF-8 between two entries.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"timestamp\":\n\xff\xfe garbage \xc3\n\n").unwrap();
+        history.record(second.clone()).unwrap();
+        assert_eq!(history.entries(), [first.clone(), second.clone()]);
+
+        let reloaded = History::load(&path);
+        assert_eq!(reloaded.path(), Some(path.as_path()));
+        assert_eq!(reloaded.entries(), [first, second]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn in_memory_history_writes_nothing() {
+        let mut history = History::in_memory();
+        history.record(entry("2026-03-01", ActionKind::Kill, 1, Outcome::Success)).unwrap();
+        assert_eq!(history.path(), None);
+        assert_eq!(history.entries().len(), 1);
+    }
+
+    #[test]
+    fn queries_filter_by_time_action_and_pid() {
+        let mut history = History::in_memory();
+        for e in [
+            entry("2026-03-01 09:00", ActionKind::Kill, 1, Outcome::Success),
+            entry("2026-03-01 10:00", ActionKind::Renice, 2, Outcome::Success),
+            entry("2026-03-01 11:00", ActionKind::Kill, 2, Outcome::Success),
+            entry("2026-03-02 00:00", ActionKind::Suspend, 3, Outcome::Success),
+        ] {
+            history.record(e).unwrap();
+        }
+        let pids = |q: HistoryQuery| history.query(&q).iter().map(|e| e.pid).collect::<Vec<_>>();
+
+        assert_eq!(pids(HistoryQuery::default()), [1, 2, 2, 3]);
+        assert_eq!(pids(HistoryQuery { since: Some(at("2026-03-01 10:00")), ..Default::default() }), [2, 2, 3]);
+        assert_eq!(pids(HistoryQuery { until: Some(at("2026-03-01 10:00")), ..Default::default() }), [1, 2]);
+        assert_eq!(pids(HistoryQuery { action: Some(ActionKind::Kill), ..Default::default() }), [1, 2]);
+        assert_eq!(pids(HistoryQuery { pid: Some(2), ..Default::default() }), [2, 2]);
+        let query = HistoryQuery {
+            since: Some(at("2026-03-01 09:30")),
+            until: Some(at("2026-03-01 23:59")),
+            action: Some(ActionKind::Kill),
+            pid: Some(2),
+        };
+        assert_eq!(history.query(&query).len(), 1);
+        assert!(history.query(&HistoryQuery { pid: Some(9), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn parses_times() {
+        let local = |y, mo, d, h, mi, s| Local.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap();
+        assert_eq!(parse_time("2026-03-01 10:05:30"), Some(local(2026, 3, 1, 10, 5, 30)));
+        assert_eq!(parse_time(" 2026-03-01 10:05 "), Some(local(2026, 3, 1, 10, 5, 0)));
+        assert_eq!(parse_time("2026-03-01"), Some(local(2026, 3, 1, 0, 0, 0)));
+        let utc = parse_time("2026-03-01T10:05:30Z").unwrap();
+        assert_eq!(utc.with_timezone(&chrono::Utc).to_rfc3339(), "2026-03-01T10:05:30+00:00");
+        let offset = parse_time("2026-03-01T12:05:30+02:00").unwrap();
+        assert_eq!(offset, utc);
+        assert_eq!(parse_time("yesterday"), None);
+        assert_eq!(parse_time("2026-13-01"), None);
+        assert_eq!(parse_time("2026-03-01 25:00"), None);
+    }
+
+    #[test]
+    fn action_names_round_trip() {
+        for &action in ActionKind::ALL {
+            assert_eq!(action.as_str().parse::<ActionKind>(), Ok(action));
+        }
+        assert_eq!("KILL".parse::<ActionKind>(), Ok(ActionKind::Kill));
+        assert!("explode".parse::<ActionKind>().is_err());
+    }
+}
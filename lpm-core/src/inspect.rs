@@ -0,0 +1,321 @@
+// src/inspect.rs for lpm-core
+//
+// Detailed, on-demand view of a single process from /proc/<pid>/*.
+
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{procfs, LpmError, ProcessManager, ProcessState};
+
+/// Capability names by bit number, as in `capabilities(7)`.
+const CAPABILITY_NAMES: &[&str] = &[
+    "chown",
+    "dac_override",
+    "dac_read_search",
+    "fowner",
+    "fsetid",
+    "kill",
+    "setgid",
+    "setuid",
+    "setpcap",
+    "linux_immutable",
+    "net_bind_service",
+    "net_broadcast",
+    "net_admin",
+    "net_raw",
+    "ipc_lock",
+    "ipc_owner",
+    "sys_module",
+    "sys_rawio",
+    "sys_chroot",
+    "sys_ptrace",
+    "sys_pacct",
+    "sys_admin",
+    "sys_boot",
+    "sys_nice",
+    "sys_resource",
+    "sys_time",
+    "sys_tty_config",
+    "mknod",
+    "lease",
+    "audit_write",
+    "audit_control",
+    "setfcap",
+    "mac_override",
+    "mac_admin",
+    "syslog",
+    "wake_alarm",
+    "block_suspend",
+    "audit_read",
+    "perfmon",
+    "bpf",
+    "checkpoint_restore",
+];
+
+/// A capability set as the bitmask shown in `/proc/<pid>/status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapSet(pub u64);
+
+impl CapSet {
+    /// Names of the capabilities in the set, e.g. `["kill", "net_admin"]`.
+    /// Bits the table does not know are shown as `cap_<n>`.
+    pub fn names(&self) -> Vec<String> {
+        (0..64)
+            .filter(|bit| self.0 & (1 << bit) != 0)
+            .map(|bit| match CAPABILITY_NAMES.get(bit) {
+                Some(name) => name.to_string(),
+                None => format!("cap_{}", bit),
+            })
+            .collect()
+    }
+
+    /// Whether every capability the kernel knows about is set.
+    fn is_full(&self) -> bool {
+        let known = (1u64 << CAPABILITY_NAMES.len()) - 1;
+        self.0 & known == known
+    }
+}
+
+impl fmt::Display for CapSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0 == 0 {
+            f.write_str("none")
+        } else if self.is_full() {
+            write!(f, "all ({:016x})", self.0)
+        } else {
+            f.write_str(&self.names().join(","))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub inheritable: CapSet,
+    pub permitted: CapSet,
+    pub effective: CapSet,
+    pub bounding: CapSet,
+    pub ambient: CapSet,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeccompMode {
+    Disabled,
+    Strict,
+    Filter,
+}
+
+impl fmt::Display for SeccompMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SeccompMode::Disabled => "disabled",
+            SeccompMode::Strict => "strict",
+            SeccompMode::Filter => "filter",
+        })
+    }
+}
+
+/// Everything [`ProcessManager::inspect`] reads about a process. Fields the
+/// caller may not read (another user's environment or cwd, for instance)
+/// are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub name: String,
+    pub state: ProcessState,
+    pub exe: Option<PathBuf>,
+    pub argv: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub root: Option<PathBuf>,
+    pub environment: Option<Vec<(String, String)>>,
+    pub start_time: Option<DateTime<Local>>,
+    pub session: i32,
+    pub pgid: i32,
+    /// Controlling terminal such as `pts/3`; `None` for daemons.
+    pub tty: Option<String>,
+    pub threads: u64,
+    pub voluntary_ctxt_switches: u64,
+    pub nonvoluntary_ctxt_switches: u64,
+    pub capabilities: Capabilities,
+    pub seccomp: Option<SeccompMode>,
+    /// cgroup v2 path; `None` on a v1-only system.
+    pub cgroup: Option<String>,
+}
+
+impl fmt::Display for ProcessInfo {
+    /// Multi-line `key: value` listing, without the environment.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = |p: &Option<PathBuf>| p.as_ref().map_or("?".to_string(), |p| p.display().to_string());
+        let caps = &self.capabilities;
+        writeln!(f, "PID:           {}", self.pid)?;
+        writeln!(f, "Parent PID:    {}", self.ppid)?;
+        writeln!(f, "Name:          {}", self.name)?;
+        writeln!(f, "State:         {}", self.state)?;
+        writeln!(f, "Executable:    {}", path(&self.exe))?;
+        writeln!(f, "Command line:  {}", self.argv.join(" "))?;
+        writeln!(f, "Working dir:   {}", path(&self.cwd))?;
+        writeln!(f, "Root:          {}", path(&self.root))?;
+        match self.start_time {
+            Some(t) => writeln!(f, "Started:       {}", t.format("%Y-%m-%d %H:%M:%S"))?,
+            None => writeln!(f, "Started:       ?")?,
+        }
+        writeln!(f, "Session:       {}", self.session)?;
+        writeln!(f, "Process group: {}", self.pgid)?;
+        writeln!(f, "TTY:           {}", self.tty.as_deref().unwrap_or("-"))?;
+        writeln!(f, "Threads:       {}", self.threads)?;
+        writeln!(
+            f,
+            "Ctx switches:  {} voluntary, {} involuntary",
+            self.voluntary_ctxt_switches, self.nonvoluntary_ctxt_switches
+        )?;
+        writeln!(f, "Cap effective: {}", caps.effective)?;
+        writeln!(f, "Cap permitted: {}", caps.permitted)?;
+        writeln!(f, "Cap inherit:   {}", caps.inheritable)?;
+        writeln!(f, "Cap bounding:  {}", caps.bounding)?;
+        writeln!(f, "Cap ambient:   {}", caps.ambient)?;
+        writeln!(f, "Seccomp:       {}", self.seccomp.map_or("?".to_string(), |s| s.to_string()))?;
+        write!(f, "Cgroup:        {}", self.cgroup.as_deref().unwrap_or("?"))
+    }
+}
+
+/// Names a controlling terminal from its device number, like ps(1).
+fn tty_name(tty_nr: u32) -> Option<String> {
+    if tty_nr == 0 {
+        return None;
+    }
+    let major = (tty_nr >> 8) & 0xfff;
+    let minor = (tty_nr & 0xff) | ((tty_nr >> 12) & 0xfff00);
+    Some(match major {
+        136..=143 => format!("pts/{}", (major - 136) * 256 + minor),
+        4 if minor < 64 => format!("tty{}", minor),
+        4 => format!("ttyS{}", minor - 64),
+        _ => format!("{}:{}", major, minor),
+    })
+}
+
+/// Converts a start time in clock ticks after boot to wall-clock time.
+fn start_time(starttime: u64) -> Option<DateTime<Local>> {
+    let stat = fs::read_to_string("/proc/stat").ok()?;
+    let btime: i64 = stat.lines().find_map(|l| l.strip_prefix("btime "))?.trim().parse().ok()?;
+    let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks <= 0 {
+        return None;
+    }
+    let millis = btime * 1000 + (starttime as i64 * 1000) / ticks as i64;
+    Local.timestamp_millis_opt(millis).single()
+}
+
+impl ProcessManager {
+    /// Reads detailed information about `pid` from /proc. Nothing is
+    /// changed, so protected processes can be inspected too.
+    pub fn inspect(&mut self, pid: usize) -> Result<ProcessInfo, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        let io_err = |e| LpmError::from_io(e, pid32);
+
+        let stat = procfs::read_stat(raw).map_err(io_err)?;
+        let status = procfs::read_status(raw).map_err(io_err)?;
+        let number = |key: &str| procfs::status_field(&status, key).and_then(|v| v.parse().ok());
+        let capset = |key: &str| {
+            CapSet(
+                procfs::status_field(&status, key)
+                    .and_then(|v| u64::from_str_radix(v, 16).ok())
+                    .unwrap_or(0),
+            )
+        };
+        let lossy = |s: std::ffi::OsString| s.to_string_lossy().into_owned();
+
+        let environment = procfs::read_nul_separated(raw, "environ").ok().map(|vars| {
+            vars.into_iter()
+                .map(|var| {
+                    let var = lossy(var);
+                    match var.split_once('=') {
+                        Some((k, v)) => (k.to_string(), v.to_string()),
+                        None => (var, String::new()),
+                    }
+                })
+                .collect()
+        });
+
+        Ok(ProcessInfo {
+            pid: pid32,
+            ppid: stat.ppid as u32,
+            name: procfs::status_field(&status, "Name:").unwrap_or_default().to_string(),
+            state: ProcessState::from_stat(stat.state),
+            exe: procfs::read_link(raw, "exe").ok(),
+            argv: procfs::read_nul_separated(raw, "cmdline")
+                .map(|args| args.into_iter().map(lossy).collect())
+                .unwrap_or_default(),
+            cwd: procfs::read_link(raw, "cwd").ok(),
+            root: procfs::read_link(raw, "root").ok(),
+            environment,
+            start_time: start_time(stat.starttime),
+            session: stat.session,
+            pgid: stat.pgrp,
+            tty: tty_name(stat.tty_nr),
+            threads: stat.num_threads,
+            voluntary_ctxt_switches: number("voluntary_ctxt_switches:").unwrap_or(0),
+            nonvoluntary_ctxt_switches: number("nonvoluntary_ctxt_switches:").unwrap_or(0),
+            capabilities: Capabilities {
+                inheritable: capset("CapInh:"),
+                permitted: capset("CapPrm:"),
+                effective: capset("CapEff:"),
+                bounding: capset("CapBnd:"),
+                ambient: capset("CapAmb:"),
+            },
+            seccomp: match number("Seccomp:") {
+                Some(0) => Some(SeccompMode::Disabled),
+                Some(1) => Some(SeccompMode::Strict),
+                Some(2) => Some(SeccompMode::Filter),
+                _ => None,
+            },
+            cgroup: procfs::read_cgroup(raw).ok().flatten(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_capability_masks() {
+        assert!(CapSet(0).names().is_empty());
+        assert_eq!(CapSet(0).to_string(), "none");
+
+        // CapEff of root on a 3.16-era kernel, up to audit_read.
+        let old_root = CapSet(0x3fffffffff).names();
+        assert_eq!(old_root.len(), 38);
+        assert_eq!(old_root.first().map(String::as_str), Some("chown"));
+        assert_eq!(old_root.last().map(String::as_str), Some("audit_read"));
+        assert!(!CapSet(0x3fffffffff).to_string().starts_with("all"));
+
+        assert_eq!(CapSet(0x1ffffffffff).to_string(), "all (000001ffffffffff)");
+        assert_eq!(CapSet(u64::MAX).to_string(), "all (ffffffffffffffff)");
+
+        let some = CapSet((1 << 5) | (1 << 10) | (1 << 21));
+        assert_eq!(some.names(), ["kill", "net_bind_service", "sys_admin"]);
+        assert_eq!(some.to_string(), "kill,net_bind_service,sys_admin");
+        assert_eq!(CapSet(1 << 41 | 1).names(), ["chown", "cap_41"]);
+    }
+
+    #[test]
+    fn names_terminals() {
+        let dev = |major: u32, minor: u32| (major << 8) | (minor & 0xff) | ((minor & !0xff) << 12);
+        assert_eq!(tty_name(0), None);
+        assert_eq!(tty_name(dev(136, 0)).as_deref(), Some("pts/0"));
+        assert_eq!(tty_name(dev(136, 7)).as_deref(), Some("pts/7"));
+        assert_eq!(tty_name(dev(137, 44)).as_deref(), Some("pts/300"));
+        assert_eq!(tty_name(dev(136, 300)).as_deref(), Some("pts/300"));
+        assert_eq!(tty_name(dev(4, 1)).as_deref(), Some("tty1"));
+        assert_eq!(tty_name(dev(4, 63)).as_deref(), Some("tty63"));
+        assert_eq!(tty_name(dev(4, 64)).as_deref(), Some("ttyS0"));
+        assert_eq!(tty_name(dev(5, 1)).as_deref(), Some("5:1"));
+        // The raw value /proc/<pid>/stat shows for pts/1.
+        assert_eq!(tty_name(34817).as_deref(), Some("pts/1"));
+    }
+}
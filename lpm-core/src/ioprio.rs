@@ -0,0 +1,236 @@
+// src/ioprio.rs for lpm-core
+//
+// I/O scheduling class and level through the ioprio_get(2)/ioprio_set(2)
+// syscalls, which libc does not wrap.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{procfs, ActionKind, LpmError, ProcessManager};
+
+const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+const IOPRIO_WHO_PGRP: libc::c_long = 2;
+const IOPRIO_CLASS_SHIFT: i32 = 13;
+/// The level is the low three bits; newer kernels keep priority hints in
+/// the bits above it.
+const IOPRIO_LEVEL_MASK: i32 = 0x7;
+
+/// An I/O scheduling class, as in ionice(1).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IoClass {
+    /// No class set; the kernel derives best-effort with a level from the
+    /// nice value.
+    #[default]
+    None,
+    /// Served first; needs CAP_SYS_ADMIN.
+    RealTime,
+    BestEffort,
+    /// Served only when no one else needs the disk.
+    Idle,
+}
+
+impl IoClass {
+    pub const ALL: &'static [IoClass] = &[IoClass::None, IoClass::RealTime, IoClass::BestEffort, IoClass::Idle];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IoClass::None => "none",
+            IoClass::RealTime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        }
+    }
+
+    /// Whether the class takes a level of 0 (highest) to 7.
+    pub fn has_level(&self) -> bool {
+        matches!(self, IoClass::RealTime | IoClass::BestEffort)
+    }
+}
+
+impl fmt::Display for IoClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for IoClass {
+    type Err = LpmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "0" => Ok(IoClass::None),
+            "realtime" | "rt" | "1" => Ok(IoClass::RealTime),
+            "best-effort" | "besteffort" | "be" | "2" => Ok(IoClass::BestEffort),
+            "idle" | "3" => Ok(IoClass::Idle),
+            _ => Err(LpmError::InvalidInput(format!("unknown I/O class '{}'", s))),
+        }
+    }
+}
+
+/// I/O class and level of a process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IoPriority {
+    pub class: IoClass,
+    /// 0 (highest) to 7 for real-time and best-effort; 0 otherwise.
+    pub level: u8,
+}
+
+impl IoPriority {
+    fn from_raw(raw: i32) -> Self {
+        let class = match raw >> IOPRIO_CLASS_SHIFT {
+            1 => IoClass::RealTime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        let level = if class.has_level() { (raw & IOPRIO_LEVEL_MASK) as u8 } else { 0 };
+        IoPriority { class, level }
+    }
+
+    fn to_raw(self) -> i32 {
+        let class = match self.class {
+            IoClass::None => 0,
+            IoClass::RealTime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        (class << IOPRIO_CLASS_SHIFT) | self.level as i32
+    }
+
+    fn validate(&self) -> Result<(), LpmError> {
+        let max = if self.class.has_level() { 7 } else { 0 };
+        if self.level > max {
+            return Err(LpmError::InvalidInput(format!(
+                "I/O level {} is invalid for {} (expected 0..={})",
+                self.level, self.class, max
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for IoPriority {
+    /// `best-effort/4`, `idle` or `none`, padded as a whole.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.class.has_level() {
+            f.pad(&format!("{}/{}", self.class, self.level))
+        } else {
+            f.pad(self.class.as_str())
+        }
+    }
+}
+
+fn ioprio_get(which: libc::c_long, who: libc::pid_t) -> Result<IoPriority, LpmError> {
+    let ret = unsafe { libc::syscall(libc::SYS_ioprio_get, which, who as libc::c_long) };
+    if ret < 0 {
+        return Err(LpmError::last_os_error(who as u32));
+    }
+    Ok(IoPriority::from_raw(ret as i32))
+}
+
+fn ioprio_set(which: libc::c_long, who: libc::pid_t, prio: IoPriority) -> Result<(), LpmError> {
+    let ret = unsafe { libc::syscall(libc::SYS_ioprio_set, which, who as libc::c_long, prio.to_raw() as libc::c_long) };
+    if ret < 0 {
+        return Err(LpmError::last_os_error(who as u32));
+    }
+    Ok(())
+}
+
+/// Reads the I/O priority of `pid` for listings; `None` if it is gone.
+pub(crate) fn read_io_priority(pid: libc::pid_t) -> Option<IoPriority> {
+    ioprio_get(IOPRIO_WHO_PROCESS, pid).ok()
+}
+
+impl ProcessManager {
+    /// The I/O class and level of the main thread of `pid`.
+    pub fn get_io_priority(&mut self, pid: usize) -> Result<IoPriority, LpmError> {
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid as u32))?;
+        ioprio_get(IOPRIO_WHO_PROCESS, raw)
+    }
+
+    /// Sets the I/O class and level of every thread of `pid`.
+    pub fn set_io_priority(&mut self, pid: usize, class: IoClass, level: u8) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let old = self.get_io_priority(pid).ok();
+        let prio = IoPriority { class, level };
+        let result = prio.validate().and_then(|()| self.check_target(pid)).and_then(|raw| {
+            ioprio_set(IOPRIO_WHO_PROCESS, raw, prio)?;
+            // I/O priority is per thread, like the scheduling policy.
+            let tids = procfs::list_tasks(raw).map_err(|e| LpmError::from_io(e, pid as u32))?;
+            for tid in tids.into_iter().filter(|&tid| tid != raw) {
+                match ioprio_set(IOPRIO_WHO_PROCESS, tid, prio) {
+                    Ok(()) | Err(LpmError::NoSuchProcess(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        });
+        let mut params = vec![("ioprio", prio.to_string())];
+        if let Some(old) = old {
+            params.push(("old", old.to_string()));
+        }
+        self.record(ActionKind::IoNice, pid, name, &params, &result);
+        result
+    }
+
+    /// Sets the I/O class and level of every process in process group
+    /// `pgid`. The group leader must pass the usual protections; the
+    /// previous value recorded is the highest priority in the group.
+    pub fn set_group_io_priority(&mut self, pgid: usize, class: IoClass, level: u8) -> Result<(), LpmError> {
+        let name = self.process_name(pgid);
+        let prio = IoPriority { class, level };
+        let old = libc::pid_t::try_from(pgid).ok().and_then(|raw| ioprio_get(IOPRIO_WHO_PGRP, raw).ok());
+        let result = prio
+            .validate()
+            .and_then(|()| self.check_target(pgid))
+            .and_then(|raw| ioprio_set(IOPRIO_WHO_PGRP, raw, prio));
+        let mut params = vec![("pgid", pgid.to_string()), ("ioprio", prio.to_string())];
+        if let Some(old) = old {
+            params.push(("old", old.to_string()));
+        }
+        self.record(ActionKind::IoNice, pgid, name, &params, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn priority(class: IoClass, level: u8) -> IoPriority {
+        IoPriority { class, level }
+    }
+
+    #[test]
+    fn from_raw_splits_class_and_level() {
+        assert_eq!(IoPriority::from_raw(0), priority(IoClass::None, 0));
+        assert_eq!(IoPriority::from_raw(1 << 13), priority(IoClass::RealTime, 0));
+        assert_eq!(IoPriority::from_raw((2 << 13) | 4), priority(IoClass::BestEffort, 4));
+        assert_eq!(IoPriority::from_raw((2 << 13) | 7), priority(IoClass::BestEffort, 7));
+        // Idle has no levels, whatever the low bits say.
+        assert_eq!(IoPriority::from_raw((3 << 13) | 5), priority(IoClass::Idle, 0));
+    }
+
+    #[test]
+    fn from_raw_ignores_priority_hints() {
+        let hint = 1 << 3;
+        assert_eq!(IoPriority::from_raw((2 << 13) | hint | 3), priority(IoClass::BestEffort, 3));
+        assert_eq!(IoPriority::from_raw((1 << 13) | (0x3ff << 3) | 1), priority(IoClass::RealTime, 1));
+    }
+
+    #[test]
+    fn to_raw_round_trips() {
+        assert_eq!(priority(IoClass::BestEffort, 4).to_raw(), (2 << 13) | 4);
+        assert_eq!(priority(IoClass::Idle, 0).to_raw(), 3 << 13);
+        assert_eq!(priority(IoClass::None, 0).to_raw(), 0);
+        for &class in IoClass::ALL {
+            let levels = if class.has_level() { 0..=7 } else { 0..=0 };
+            for level in levels {
+                let p = priority(class, level);
+                assert_eq!(IoPriority::from_raw(p.to_raw()), p);
+            }
+        }
+    }
+}
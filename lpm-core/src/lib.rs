@@ -3,52 +3,189 @@
 use sysinfo::{System, SystemExt, ProcessExt, Pid, PidExt};
 use chrono::Local;
 use libc::{setpriority, PRIO_PROCESS};
+use std::path::PathBuf;
+use threads::ThreadTimes;
+use users::NameCache;
+
+mod affinity;
+mod alerts;
+mod cgroup;
+mod error;
+mod export;
+mod fds;
+mod history;
+mod inspect;
+mod ioprio;
+mod limits;
+mod memory;
+mod metrics;
+mod net;
+mod oom;
+mod procfs;
+mod query;
+mod remediation;
+mod restart;
+mod sched;
+mod signal;
+mod snapshot;
+mod suspend;
+mod terminate;
+mod threads;
+mod tree;
+mod users;
+
+pub use affinity::{format_cpu_list, online_cpus, parse_cpu_list};
+pub use alerts::{
+    Alert, AlertEngine, AlertRule, AlertStatus, Comparison, Metric, ProcessMatcher, Severity,
+};
+pub use cgroup::{default_cgroup_root, CgroupInfo, CgroupLimit};
+pub use error::LpmError;
+pub use export::{write_processes, Column, ExportFormat};
+pub use fds::{FdKind, FileHolder, OpenFile, OpenFiles};
+pub use history::{
+    default_history_path, parse_time, ActionKind, History, HistoryEntry, HistoryQuery, Outcome,
+};
+pub use inspect::{CapSet, Capabilities, ProcessInfo, SeccompMode};
+pub use ioprio::{IoClass, IoPriority};
+pub use limits::{Limit, Resource};
+pub use memory::{Mapping, MemoryUsage};
+pub use metrics::{MetricStore, Retention, Sample};
+pub use net::{Connection, Protocol, SocketState};
+pub use oom::{OOM_SCORE_ADJ_MAX, OOM_SCORE_ADJ_MIN};
+pub use query::{Query, QueryError};
+pub use remediation::Remediation;
+pub use restart::RestartOptions;
+pub use sched::{SchedPolicy, Scheduling};
+pub use signal::Signal;
+pub use snapshot::{DiskIo, ProcessSnapshot, ProcessState};
+pub use terminate::{TermStage, DEFAULT_GRACE};
+pub use threads::ThreadInfo;
+pub use tree::{PidResult, TreeOrder};
+pub use users::{group_name, resolve_group, resolve_user, user_name};
+
+/// kthreadd, the parent of every kernel thread.
+const KTHREADD_PID: u32 = 2;
 
 pub struct ProcessManager {
     system: System,
-    history: Vec<String>,
+    names: NameCache,
+    history: History,
+    alerts: AlertEngine,
+    metrics: MetricStore,
+    thread_times: ThreadTimes,
+    /// Directory cgroup paths are resolved against.
+    cgroup_root: PathBuf,
+    /// Name of the alert rule whose remediation is running, if any.
+    trigger: Option<String>,
+}
+
+impl Default for ProcessManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ProcessManager {
+    /// Creates a manager whose history is loaded from, and appended to,
+    /// [`default_history_path`].
     pub fn new() -> Self {
+        let history = default_history_path().map_or_else(History::in_memory, History::load);
+        Self::with_history(history)
+    }
+
+    pub fn with_history(history: History) -> Self {
         let mut system = System::new_all();
         system.refresh_all();
-        Self { system, history: vec![] }
+        Self {
+            system,
+            names: NameCache::default(),
+            history,
+            alerts: AlertEngine::new(AlertRule::defaults()),
+            metrics: MetricStore::default(),
+            thread_times: ThreadTimes::default(),
+            cgroup_root: default_cgroup_root(),
+            trigger: None,
+        }
     }
 
-    pub fn list_processes(&mut self) -> Vec<&sysinfo::Process> {
+    /// Refreshes and lists all processes. Metric samples are only taken by
+    /// [`ProcessManager::sample_metrics`].
+    pub fn list_processes(&mut self) -> Vec<ProcessSnapshot> {
         self.system.refresh_processes();
-        self.system.processes().values().collect()
+        self.system
+            .processes()
+            .values()
+            .map(|p| ProcessSnapshot::capture(&mut self.names, p))
+            .collect()
     }
 
-    pub fn list_processes_by_name(&mut self, name: &str) -> Vec<&sysinfo::Process> {
+    pub fn list_processes_by_name(&mut self, name: &str) -> Vec<ProcessSnapshot> {
+        let name = name.to_lowercase();
         self.list_processes()
             .into_iter()
-            .filter(|p| p.name().to_lowercase().contains(&name.to_lowercase()))
+            .filter(|p| p.name.to_lowercase().contains(&name))
             .collect()
     }
 
-    pub fn list_processes_by_user(&mut self, user: &str) -> Vec<&sysinfo::Process> {
+    /// Processes whose real or effective user is `user`, given as a name or
+    /// a numeric uid.
+    pub fn list_processes_by_user(&mut self, user: &str) -> Vec<ProcessSnapshot> {
         self.list_processes()
             .into_iter()
-            .filter(|p| p.user_id().map_or(false, |u| u.to_string() == user))
+            .filter(|p| p.matches_user(user))
             .collect()
     }
 
-    pub fn kill_process(&mut self, pid: usize) -> bool {
+    /// Processes that are members of `group` (a name or a numeric gid)
+    /// through their real, effective or supplementary groups.
+    pub fn list_processes_by_group(&mut self, group: &str) -> Result<Vec<ProcessSnapshot>, LpmError> {
+        let gid = resolve_group(group)
+            .ok_or_else(|| LpmError::InvalidInput(format!("unknown group '{}'", group)))?;
+        Ok(self.list_processes().into_iter().filter(|p| p.in_group(gid)).collect())
+    }
+
+    /// Refreshes a single process and returns its snapshot, if it still exists.
+    pub fn snapshot(&mut self, pid: usize) -> Option<ProcessSnapshot> {
         let pid = Pid::from(pid);
-        if let Some(p) = self.system.process(pid) {
-            let result = p.kill();
-            self.history.push(format!("{} KILLED PID {}", Local::now(), pid));
-            result
-        } else {
-            false
+        if !self.system.refresh_process(pid) {
+            return None;
         }
+        self.system
+            .process(pid)
+            .map(|p| ProcessSnapshot::capture(&mut self.names, p))
+    }
+
+    pub fn kill_process(&mut self, pid: usize) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = self.deliver(pid, Signal::KILL);
+        self.record(ActionKind::Kill, pid, name, &[], &result);
+        result
     }
 
-    pub fn change_priority(&mut self, pid: usize, new_nice: i32) -> bool {
-        let result = unsafe { setpriority(PRIO_PROCESS, pid as u32, new_nice) == 0 };
-        self.history.push(format!("{} CHANGED PRIORITY PID {} TO {}", Local::now(), pid, new_nice));
+    /// Sends `signal` to `pid`, subject to the same protections as
+    /// [`kill_process`](Self::kill_process).
+    pub fn send_signal(&mut self, pid: usize, signal: Signal) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = self.deliver(pid, signal);
+        self.record(ActionKind::Signal, pid, name, &[("signal", signal.name())], &result);
+        result
+    }
+
+    fn deliver(&mut self, pid: usize, signal: Signal) -> Result<(), LpmError> {
+        let raw = self.check_target(pid)?;
+        error::check_os(unsafe { libc::kill(raw, signal.as_raw()) }, pid as u32)
+    }
+
+    pub fn change_priority(&mut self, pid: usize, new_nice: i32) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = if (-20..=19).contains(&new_nice) {
+            self.check_target(pid).and_then(|raw| {
+                error::check_os(unsafe { setpriority(PRIO_PROCESS, raw as libc::id_t, new_nice) }, pid as u32)
+            })
+        } else {
+            Err(LpmError::InvalidNice(new_nice))
+        };
+        self.record(ActionKind::Renice, pid, name, &[("nice", new_nice.to_string())], &result);
         result
     }
 
@@ -65,47 +202,66 @@ impl ProcessManager {
             .collect()
     }
 
-    pub fn restart_process(&mut self, pid: usize) -> bool {
-        if let Some(p) = self.system.process(Pid::from(pid)) {
-            let cmd = p.cmd().join(" ");
-            let _ = std::process::Command::new("kill").arg("-9").arg(pid.to_string()).status();
-            self.history.push(format!("{} RESTARTED PID {}", Local::now(), pid));
-            std::process::Command::new("sh")
-                .arg("-c")
-                .arg(cmd)
-                .spawn()
-                .is_ok()
+    pub fn history(&self, query: &HistoryQuery) -> Vec<&HistoryEntry> {
+        self.history.query(query)
+    }
+
+    pub fn show_history(&self, query: &HistoryQuery) -> String {
+        let entries = self.history(query);
+        if entries.is_empty() {
+            "No history yet.".to_string()
         } else {
-            false
+            entries.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("\n")
         }
     }
 
-    pub fn export_processes(&mut self, _format: &str, file_path: &str) -> Result<(), String> {
-        let data = self.list_processes()
-            .into_iter()
-            .map(|p| format!("[{}] {} ({}% CPU, {} KB Memory, USER: {:?})", p.pid(), p.name(), p.cpu_usage(), p.memory(), p.user_id()))
-            .collect::<Vec<String>>()
-            .join("\n");
-        std::fs::write(file_path, data).map_err(|e| e.to_string())
+    fn record<T>(
+        &mut self,
+        action: ActionKind,
+        pid: usize,
+        name: Option<String>,
+        params: &[(&str, String)],
+        result: &Result<T, LpmError>,
+    ) {
+        let entry = HistoryEntry {
+            timestamp: Local::now(),
+            action,
+            pid: pid as u32,
+            name,
+            params: params.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+            outcome: result.into(),
+            uid: unsafe { libc::getuid() },
+            trigger: self.trigger.clone(),
+        };
+        // Failing to persist history must not turn a completed action into an error.
+        let _ = self.history.record(entry);
     }
 
-    pub fn check_alerts(&mut self, cpu_threshold: f32, mem_threshold: u64) -> Vec<String> {
-        self.list_processes()
-            .into_iter()
-            .filter(|p| p.cpu_usage() > cpu_threshold || p.memory() > mem_threshold)
-            .map(|p| format!(
-                "ALERT: [{}] {} CPU: {}% MEM: {} KB",
-                p.pid(), p.name(), p.cpu_usage(), p.memory()
-            ))
-            .collect()
+    /// Refreshes `pid` and returns its name, for labelling history entries.
+    fn process_name(&mut self, pid: usize) -> Option<String> {
+        let sys_pid = Pid::from(pid);
+        self.system.refresh_process(sys_pid);
+        self.system.process(sys_pid).map(|p| p.name().to_string())
     }
 
-    pub fn show_history(&self) -> String {
-        if self.history.is_empty() {
-            "No history yet.".to_string()
-        } else {
-            self.history.join("\n")
+    /// Validates `pid` as an action target and converts it to a `pid_t`.
+    ///
+    /// pid 0 and negative values would address process groups in kill(2), so
+    /// they are rejected along with init, kernel threads and lpm itself.
+    fn check_target(&mut self, pid: usize) -> Result<libc::pid_t, LpmError> {
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid as u32))?;
+        let pid32 = raw as u32;
+        if pid32 <= 1 || pid32 == KTHREADD_PID || pid32 == std::process::id() {
+            return Err(LpmError::ProtectedProcess(pid32));
+        }
+        let sys_pid = Pid::from(pid);
+        if !self.system.refresh_process(sys_pid) {
+            return Err(LpmError::NoSuchProcess(pid32));
         }
+        let parent = self.system.process(sys_pid).and_then(|p| p.parent());
+        if parent.map(|pp| pp.as_u32()) == Some(KTHREADD_PID) {
+            return Err(LpmError::ProtectedProcess(pid32));
+        }
+        Ok(raw)
     }
 }
-
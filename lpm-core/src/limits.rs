@@ -0,0 +1,392 @@
+// src/limits.rs for lpm-core
+//
+// Resource limits of running processes, read from /proc/<pid>/limits and
+// changed through prlimit(2).
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+use crate::{error, procfs, ActionKind, LpmError, ProcessManager};
+
+/// A resource governed by an rlimit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resource {
+    /// Address space.
+    As,
+    /// Largest core dump; 0 disables them.
+    Core,
+    /// CPU time; SIGXCPU is sent at the soft limit.
+    Cpu,
+    Data,
+    FileSize,
+    /// flock(2) and fcntl(2) locks.
+    Locks,
+    MemLock,
+    /// Bytes in POSIX message queues of the process's real user.
+    MsgQueue,
+    /// Ceiling for raising the nice value, as `20 - nice`.
+    Nice,
+    /// One more than the highest file descriptor number that can be opened.
+    NoFile,
+    /// Processes and threads of the process's real user.
+    NProc,
+    /// No effect on current kernels.
+    Rss,
+    RtPriority,
+    /// CPU time a real-time task may use without blocking.
+    RtTime,
+    SigPending,
+    Stack,
+}
+
+impl Resource {
+    pub const ALL: &'static [Resource] = &[
+        Resource::As,
+        Resource::Core,
+        Resource::Cpu,
+        Resource::Data,
+        Resource::FileSize,
+        Resource::Locks,
+        Resource::MemLock,
+        Resource::MsgQueue,
+        Resource::Nice,
+        Resource::NoFile,
+        Resource::NProc,
+        Resource::Rss,
+        Resource::RtPriority,
+        Resource::RtTime,
+        Resource::SigPending,
+        Resource::Stack,
+    ];
+
+    /// The name used by prlimit(1), e.g. `NOFILE`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resource::As => "AS",
+            Resource::Core => "CORE",
+            Resource::Cpu => "CPU",
+            Resource::Data => "DATA",
+            Resource::FileSize => "FSIZE",
+            Resource::Locks => "LOCKS",
+            Resource::MemLock => "MEMLOCK",
+            Resource::MsgQueue => "MSGQUEUE",
+            Resource::Nice => "NICE",
+            Resource::NoFile => "NOFILE",
+            Resource::NProc => "NPROC",
+            Resource::Rss => "RSS",
+            Resource::RtPriority => "RTPRIO",
+            Resource::RtTime => "RTTIME",
+            Resource::SigPending => "SIGPENDING",
+            Resource::Stack => "STACK",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Resource::As => "address space limit",
+            Resource::Core => "max core file size",
+            Resource::Cpu => "CPU time",
+            Resource::Data => "max data size",
+            Resource::FileSize => "max file size",
+            Resource::Locks => "max number of file locks held",
+            Resource::MemLock => "max locked-in-memory address space",
+            Resource::MsgQueue => "max bytes in POSIX mqueues",
+            Resource::Nice => "max nice prio allowed to raise",
+            Resource::NoFile => "max number of open files",
+            Resource::NProc => "max number of processes",
+            Resource::Rss => "max resident set size",
+            Resource::RtPriority => "max real-time priority",
+            Resource::RtTime => "timeout for real-time tasks",
+            Resource::SigPending => "max number of pending signals",
+            Resource::Stack => "max stack size",
+        }
+    }
+
+    /// Unit of the limit values; empty for plain numbers.
+    pub fn units(&self) -> &'static str {
+        match self {
+            Resource::Cpu => "seconds",
+            Resource::Locks => "locks",
+            Resource::NoFile => "files",
+            Resource::NProc => "processes",
+            Resource::RtTime => "microseconds",
+            Resource::SigPending => "signals",
+            Resource::Nice | Resource::RtPriority => "",
+            _ => "bytes",
+        }
+    }
+
+    /// The row label in /proc/<pid>/limits.
+    fn proc_name(&self) -> &'static str {
+        match self {
+            Resource::As => "Max address space",
+            Resource::Core => "Max core file size",
+            Resource::Cpu => "Max cpu time",
+            Resource::Data => "Max data size",
+            Resource::FileSize => "Max file size",
+            Resource::Locks => "Max file locks",
+            Resource::MemLock => "Max locked memory",
+            Resource::MsgQueue => "Max msgqueue size",
+            Resource::Nice => "Max nice priority",
+            Resource::NoFile => "Max open files",
+            Resource::NProc => "Max processes",
+            Resource::Rss => "Max resident set",
+            Resource::RtPriority => "Max realtime priority",
+            Resource::RtTime => "Max realtime timeout",
+            Resource::SigPending => "Max pending signals",
+            Resource::Stack => "Max stack size",
+        }
+    }
+
+    fn as_raw(&self) -> libc::__rlimit_resource_t {
+        match self {
+            Resource::As => libc::RLIMIT_AS,
+            Resource::Core => libc::RLIMIT_CORE,
+            Resource::Cpu => libc::RLIMIT_CPU,
+            Resource::Data => libc::RLIMIT_DATA,
+            Resource::FileSize => libc::RLIMIT_FSIZE,
+            Resource::Locks => libc::RLIMIT_LOCKS,
+            Resource::MemLock => libc::RLIMIT_MEMLOCK,
+            Resource::MsgQueue => libc::RLIMIT_MSGQUEUE,
+            Resource::Nice => libc::RLIMIT_NICE,
+            Resource::NoFile => libc::RLIMIT_NOFILE,
+            Resource::NProc => libc::RLIMIT_NPROC,
+            Resource::Rss => libc::RLIMIT_RSS,
+            Resource::RtPriority => libc::RLIMIT_RTPRIO,
+            Resource::RtTime => libc::RLIMIT_RTTIME,
+            Resource::SigPending => libc::RLIMIT_SIGPENDING,
+            Resource::Stack => libc::RLIMIT_STACK,
+        }
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Resource {
+    type Err = LpmError;
+
+    /// Accepts `NOFILE`, `nofile` or `RLIMIT_NOFILE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_uppercase();
+        let name = upper.strip_prefix("RLIMIT_").unwrap_or(&upper);
+        Resource::ALL
+            .iter()
+            .copied()
+            .find(|r| r.as_str() == name)
+            .ok_or_else(|| LpmError::InvalidInput(format!("unknown resource '{}'", s)))
+    }
+}
+
+/// Soft and hard value of one resource limit. `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Limit {
+    pub resource: Resource,
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+fn value_label(value: Option<u64>) -> String {
+    value.map_or_else(|| "unlimited".to_string(), |v| v.to_string())
+}
+
+fn parse_value(s: &str) -> Result<Option<u64>, LpmError> {
+    match s.trim() {
+        "unlimited" | "infinity" | "-1" => Ok(None),
+        v => v
+            .parse::<u64>()
+            .map(|v| Some(v).filter(|&v| v != libc::RLIM_INFINITY))
+            .map_err(|_| LpmError::InvalidInput(format!("invalid limit value '{}'", s))),
+    }
+}
+
+impl Limit {
+    pub fn soft_label(&self) -> String {
+        value_label(self.soft)
+    }
+
+    pub fn hard_label(&self) -> String {
+        value_label(self.hard)
+    }
+
+    /// Applies a value in prlimit(1) syntax to this limit: `N` sets both
+    /// limits, `soft:hard` sets them separately and an empty side, as in
+    /// `1024:`, is kept. Values may be `unlimited`.
+    pub fn with_spec(&self, spec: &str) -> Result<Limit, LpmError> {
+        let (soft, hard) = match spec.split_once(':') {
+            Some((soft, hard)) => (soft, hard),
+            None => (spec, spec),
+        };
+        let side = |s: &str, current| if s.is_empty() { Ok(current) } else { parse_value(s) };
+        Ok(Limit { resource: self.resource, soft: side(soft, self.soft)?, hard: side(hard, self.hard)? })
+    }
+
+    fn validate(&self) -> Result<(), LpmError> {
+        let exceeds = match (self.soft, self.hard) {
+            (None, Some(_)) => true,
+            (Some(soft), Some(hard)) => soft > hard,
+            _ => false,
+        };
+        if exceeds {
+            return Err(LpmError::InvalidInput(format!(
+                "soft limit {} of {} exceeds hard limit {}",
+                self.soft_label(),
+                self.resource,
+                self.hard_label()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Limit {
+    /// `soft:hard`, the form [`Limit::with_spec`] accepts.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.soft_label(), self.hard_label())
+    }
+}
+
+impl ProcessManager {
+    /// Every resource limit of `pid` in [`Resource::ALL`] order. Limits the
+    /// running kernel does not report are left out.
+    pub fn limits(&mut self, pid: usize) -> Result<Vec<Limit>, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        let content =
+            fs::read_to_string(format!("/proc/{}/limits", raw)).map_err(|e| LpmError::from_io(e, pid32))?;
+        Ok(Resource::ALL
+            .iter()
+            .filter_map(|&resource| {
+                let (soft, hard) = procfs::parse_limit(&content, resource.proc_name()).ok()?;
+                Some(Limit { resource, soft, hard })
+            })
+            .collect())
+    }
+
+    pub fn get_limit(&mut self, pid: usize, resource: Resource) -> Result<Limit, LpmError> {
+        self.limits(pid)?
+            .into_iter()
+            .find(|l| l.resource == resource)
+            .ok_or_else(|| LpmError::InvalidInput(format!("{} is not reported by this kernel", resource)))
+    }
+
+    /// Sets the soft and hard limit of `resource` for `pid`. Raising a hard
+    /// limit, or changing another user's process, needs CAP_SYS_RESOURCE.
+    pub fn set_limit(
+        &mut self,
+        pid: usize,
+        resource: Resource,
+        soft: Option<u64>,
+        hard: Option<u64>,
+    ) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let old = self.get_limit(pid, resource).ok();
+        let limit = Limit { resource, soft, hard };
+        let result = limit.validate().and_then(|()| self.check_target(pid)).and_then(|raw| {
+            let new = libc::rlimit {
+                rlim_cur: soft.unwrap_or(libc::RLIM_INFINITY),
+                rlim_max: hard.unwrap_or(libc::RLIM_INFINITY),
+            };
+            let ret = unsafe { libc::prlimit(raw, resource.as_raw(), &new, std::ptr::null_mut()) };
+            error::check_os(ret, pid as u32)
+        });
+        let mut params = vec![
+            ("resource", resource.to_string()),
+            ("soft", limit.soft_label()),
+            ("hard", limit.hard_label()),
+        ];
+        if let Some(old) = old {
+            params.push(("old", old.to_string()));
+        }
+        self.record(ActionKind::Limit, pid, name, &params, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: &str = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max file size             unlimited            unlimited            bytes     
+Max data size             unlimited            unlimited            bytes     
+Max stack size            8388608              unlimited            bytes     
+Max core file size        0                    unlimited            bytes     
+Max resident set          unlimited            unlimited            bytes     
+Max processes             63432                63432                processes 
+Max open files            1024                 524288               files     
+Max locked memory         8388608              8388608              bytes     
+Max address space         unlimited            unlimited            bytes     
+Max file locks            unlimited            unlimited            locks     
+Max pending signals       63432                63432                signals   
+Max msgqueue size         819200               819200               bytes     
+Max nice priority         0                    0                    
+Max realtime priority     0                    0                    
+Max realtime timeout      unlimited            unlimited            us        
+";
+
+    fn nofile(soft: Option<u64>, hard: Option<u64>) -> Limit {
+        Limit { resource: Resource::NoFile, soft, hard }
+    }
+
+    #[test]
+    fn parses_proc_limits() {
+        let parse = |r: Resource| procfs::parse_limit(LIMITS, r.proc_name()).unwrap();
+        assert_eq!(parse(Resource::NoFile), (Some(1024), Some(524288)));
+        assert_eq!(parse(Resource::Stack), (Some(8388608), None));
+        assert_eq!(parse(Resource::Cpu), (None, None));
+        assert_eq!(parse(Resource::Core), (Some(0), None));
+        assert_eq!(parse(Resource::RtPriority), (Some(0), Some(0)));
+        assert_eq!(parse(Resource::RtTime), (None, None));
+        for resource in Resource::ALL {
+            assert!(procfs::parse_limit(LIMITS, resource.proc_name()).is_ok(), "{}", resource);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_proc_limits() {
+        assert!(procfs::parse_limit(LIMITS, "Max widgets").is_err());
+        assert!(procfs::parse_limit("Max open files            1024\n", "Max open files").is_err());
+        assert!(procfs::parse_limit("Max open files            lots  lots\n", "Max open files").is_err());
+    }
+
+    #[test]
+    fn applies_specs() {
+        let current = nofile(Some(1024), Some(524288));
+        assert_eq!(current.with_spec("4096").unwrap(), nofile(Some(4096), Some(4096)));
+        assert_eq!(current.with_spec("2048:8192").unwrap(), nofile(Some(2048), Some(8192)));
+        assert_eq!(current.with_spec(":8192").unwrap(), nofile(Some(1024), Some(8192)));
+        assert_eq!(current.with_spec("2048:").unwrap(), nofile(Some(2048), Some(524288)));
+        assert_eq!(current.with_spec("unlimited").unwrap(), nofile(None, None));
+        assert_eq!(current.with_spec("1024:infinity").unwrap(), nofile(Some(1024), None));
+        assert_eq!(current.with_spec("-1:-1").unwrap(), nofile(None, None));
+        assert_eq!(current.with_spec(&libc::RLIM_INFINITY.to_string()).unwrap(), nofile(None, None));
+        assert!(matches!(current.with_spec("many"), Err(LpmError::InvalidInput(_))));
+        assert!(matches!(current.with_spec("1:2:3"), Err(LpmError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn soft_may_not_exceed_hard() {
+        let current = nofile(Some(1024), Some(4096));
+        assert!(current.with_spec("2048").unwrap().validate().is_ok());
+        assert!(current.with_spec("unlimited").unwrap().validate().is_ok());
+        assert!(current.with_spec("8192:").unwrap().validate().is_err());
+        assert!(current.with_spec("unlimited:").unwrap().validate().is_err());
+        assert!(current.with_spec(":512").unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn displays_as_a_spec() {
+        let limit = nofile(Some(1024), None);
+        assert_eq!(limit.to_string(), "1024:unlimited");
+        assert_eq!(nofile(None, None).with_spec(&limit.to_string()).unwrap(), limit);
+    }
+}
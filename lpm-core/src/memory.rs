@@ -0,0 +1,305 @@
+// src/memory.rs for lpm-core
+//
+// Proportional and unique memory accounting from /proc/<pid>/smaps_rollup
+// and /proc/<pid>/smaps.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::{LpmError, ProcessManager};
+
+/// Memory counters of a process or a mapping, in bytes.
+///
+/// Unlike RSS, PSS splits each shared page between the processes mapping
+/// it, so PSS summed over all processes is the memory actually in use. USS
+/// is what would be freed if the process exited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    pub rss: u64,
+    pub pss: u64,
+    /// Private clean plus private dirty.
+    pub uss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    pub anonymous: u64,
+    pub swap: u64,
+    /// Swap split between sharers like PSS.
+    pub swap_pss: u64,
+}
+
+impl MemoryUsage {
+    /// Adds one `Key:  N kB` line of smaps; other lines are ignored.
+    fn add_line(&mut self, line: &str) {
+        let mut fields = line.split_whitespace();
+        let (Some(key), Some(value), Some("kB")) = (fields.next(), fields.next(), fields.next()) else {
+            return;
+        };
+        let Ok(kb) = value.parse::<u64>() else {
+            return;
+        };
+        let bytes = kb * 1024;
+        match key {
+            "Rss:" => self.rss += bytes,
+            "Pss:" => self.pss += bytes,
+            "Shared_Clean:" => self.shared_clean += bytes,
+            "Shared_Dirty:" => self.shared_dirty += bytes,
+            "Private_Clean:" => {
+                self.private_clean += bytes;
+                self.uss += bytes;
+            }
+            "Private_Dirty:" => {
+                self.private_dirty += bytes;
+                self.uss += bytes;
+            }
+            "Anonymous:" => self.anonymous += bytes,
+            "Swap:" => self.swap += bytes,
+            "SwapPss:" => self.swap_pss += bytes,
+            _ => {}
+        }
+    }
+
+    fn add(&mut self, other: &MemoryUsage) {
+        self.rss += other.rss;
+        self.pss += other.pss;
+        self.uss += other.uss;
+        self.shared_clean += other.shared_clean;
+        self.shared_dirty += other.shared_dirty;
+        self.private_clean += other.private_clean;
+        self.private_dirty += other.private_dirty;
+        self.anonymous += other.anonymous;
+        self.swap += other.swap;
+        self.swap_pss += other.swap_pss;
+    }
+}
+
+impl fmt::Display for MemoryUsage {
+    /// Multi-line `key: value` listing in KB.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "RSS:           {} KB", self.rss / 1024)?;
+        writeln!(f, "PSS:           {} KB", self.pss / 1024)?;
+        writeln!(f, "USS:           {} KB", self.uss / 1024)?;
+        writeln!(f, "Shared clean:  {} KB", self.shared_clean / 1024)?;
+        writeln!(f, "Shared dirty:  {} KB", self.shared_dirty / 1024)?;
+        writeln!(f, "Private clean: {} KB", self.private_clean / 1024)?;
+        writeln!(f, "Private dirty: {} KB", self.private_dirty / 1024)?;
+        writeln!(f, "Anonymous:     {} KB", self.anonymous / 1024)?;
+        writeln!(f, "Swap:          {} KB", self.swap / 1024)?;
+        write!(f, "Swap PSS:      {} KB", self.swap_pss / 1024)
+    }
+}
+
+/// One mapping from /proc/<pid>/smaps, or several merged by
+/// [`ProcessManager::top_mappings`], whose range then spans all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mapping {
+    pub start: u64,
+    pub end: u64,
+    /// Permissions such as `r-xp`; empty for merged mappings.
+    pub perms: String,
+    /// Mapped file, or a pseudo-name like `[heap]`; `[anon]` for anonymous
+    /// memory.
+    pub path: String,
+    pub usage: MemoryUsage,
+}
+
+impl Mapping {
+    /// Parses a header line: `start-end perms offset dev inode [path]`.
+    fn parse_header(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, char::is_whitespace);
+        let (start, end) = fields.next()?.split_once('-')?;
+        let perms = fields.next()?;
+        let path = fields.nth(3).map(str::trim).filter(|p| !p.is_empty()).unwrap_or("[anon]");
+        Some(Mapping {
+            start: u64::from_str_radix(start, 16).ok()?,
+            end: u64::from_str_radix(end, 16).ok()?,
+            perms: perms.to_string(),
+            path: path.to_string(),
+            usage: MemoryUsage::default(),
+        })
+    }
+
+    /// Size of the address range in bytes.
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+fn parse_smaps(content: &str) -> Vec<Mapping> {
+    let mut mappings: Vec<Mapping> = Vec::new();
+    for line in content.lines() {
+        // Counter lines start with a `Key:`; everything else is a header.
+        if line.split_whitespace().next().is_some_and(|key| key.ends_with(':')) {
+            if let Some(mapping) = mappings.last_mut() {
+                mapping.usage.add_line(line);
+            }
+        } else if let Some(mapping) = Mapping::parse_header(line) {
+            mappings.push(mapping);
+        }
+    }
+    mappings
+}
+
+fn read_smaps(pid: libc::pid_t) -> io::Result<Vec<Mapping>> {
+    Ok(parse_smaps(&fs::read_to_string(format!("/proc/{}/smaps", pid))?))
+}
+
+impl ProcessManager {
+    /// RSS, PSS, USS and swap of `pid` from smaps_rollup, or by summing
+    /// smaps on kernels before 4.14. Another user's process needs ptrace
+    /// privileges.
+    pub fn memory_usage(&mut self, pid: usize) -> Result<MemoryUsage, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        let io_err = |e| LpmError::from_io(e, pid32);
+        let mut usage = MemoryUsage::default();
+        match fs::read_to_string(format!("/proc/{}/smaps_rollup", raw)) {
+            Ok(rollup) => rollup.lines().for_each(|l| usage.add_line(l)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && fs::metadata(format!("/proc/{}", raw)).is_ok() => {
+                for mapping in read_smaps(raw).map_err(io_err)? {
+                    usage.add(&mapping.usage);
+                }
+            }
+            Err(e) => return Err(io_err(e)),
+        }
+        Ok(usage)
+    }
+
+    /// Every mapping of `pid` with its own counters, in address order.
+    pub fn memory_mappings(&mut self, pid: usize) -> Result<Vec<Mapping>, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        read_smaps(raw).map_err(|e| LpmError::from_io(e, pid32))
+    }
+
+    /// The `limit` largest users of memory in `pid` by PSS, with the
+    /// mappings of each file (text, data, ...) merged into one entry.
+    pub fn top_mappings(&mut self, pid: usize, limit: usize) -> Result<Vec<Mapping>, LpmError> {
+        let mut merged: HashMap<String, Mapping> = HashMap::new();
+        for m in self.memory_mappings(pid)? {
+            let entry = merged.entry(m.path.clone()).or_insert_with(|| Mapping {
+                start: m.start,
+                end: m.start,
+                perms: String::new(),
+                path: m.path.clone(),
+                usage: MemoryUsage::default(),
+            });
+            entry.start = entry.start.min(m.start);
+            entry.end = entry.end.max(m.end);
+            entry.usage.add(&m.usage);
+        }
+        let mut top: Vec<Mapping> = merged.into_values().collect();
+        top.sort_by_key(|m| std::cmp::Reverse(m.usage.pss));
+        top.truncate(limit);
+        Ok(top)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMAPS: &str = "\
+55d0c09ff000-55d0c0a01000 r-xp 00002000 fd:01 1835027                    /usr/bin/cat
+Size:                  8 kB
+KernelPageSize:        4 kB
+Rss:                   8 kB
+Pss:                   2 kB
+Shared_Clean:          8 kB
+Shared_Dirty:          0 kB
+Private_Clean:         0 kB
+Private_Dirty:         0 kB
+Anonymous:             0 kB
+Swap:                  0 kB
+SwapPss:               0 kB
+VmFlags: rd ex mr mw me
+55d0c0a00000-55d0c0a21000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                  12 kB
+Pss:                  12 kB
+Shared_Clean:          0 kB
+Shared_Dirty:          0 kB
+Private_Clean:         4 kB
+Private_Dirty:         8 kB
+Anonymous:            12 kB
+Swap:                 16 kB
+SwapPss:              16 kB
+THPeligible:    0
+VmFlags: rd wr mr mw me ac
+7f3a1c000000-7f3a1c021000 rw-p 00000000 00:00 0 
+Size:                132 kB
+Rss:                   4 kB
+Pss:                   4 kB
+Private_Dirty:         4 kB
+Anonymous:             4 kB
+Swap:                  4 kB
+SwapPss:               2 kB
+7f3a1d000000-7f3a1d001000 r--s 00000000 00:05 2048                       /dev/shm/my cache (deleted)
+Rss:                   4 kB
+Pss:                   1 kB
+Shared_Clean:          4 kB
+";
+
+    #[test]
+    fn parses_headers() {
+        let heap = Mapping::parse_header("55d0c0a00000-55d0c0a21000 rw-p 00000000 00:00 0                          [heap]").unwrap();
+        assert_eq!((heap.start, heap.end, heap.size()), (0x55d0c0a00000, 0x55d0c0a21000, 0x21000));
+        assert_eq!(heap.perms, "rw-p");
+        assert_eq!(heap.path, "[heap]");
+        assert_eq!(heap.usage, MemoryUsage::default());
+
+        let anon = Mapping::parse_header("7f3a1c000000-7f3a1c021000 rw-p 00000000 00:00 0").unwrap();
+        assert_eq!(anon.path, "[anon]");
+        let padded = Mapping::parse_header("7f3a1c000000-7f3a1c021000 rw-p 00000000 00:00 0    ").unwrap();
+        assert_eq!(padded.path, "[anon]");
+
+        assert_eq!(Mapping::parse_header("VmFlags: rd wr"), None);
+        assert_eq!(Mapping::parse_header("zzzz-0000 rw-p"), None);
+    }
+
+    #[test]
+    fn parses_mappings_and_their_counters() {
+        let mappings = parse_smaps(SMAPS);
+        let paths: Vec<&str> = mappings.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, ["/usr/bin/cat", "[heap]", "[anon]", "/dev/shm/my cache (deleted)"]);
+
+        let heap = &mappings[1].usage;
+        assert_eq!(heap.rss, 12 * 1024);
+        assert_eq!(heap.uss, 12 * 1024);
+        assert_eq!(heap.private_clean, 4 * 1024);
+        assert_eq!(heap.private_dirty, 8 * 1024);
+        assert_eq!(heap.anonymous, 12 * 1024);
+        assert_eq!(heap.swap, 16 * 1024);
+
+        assert_eq!(mappings[3].usage.shared_clean, 4 * 1024);
+        assert_eq!(mappings[3].usage.uss, 0);
+    }
+
+    #[test]
+    fn sums_like_smaps_rollup() {
+        let mut total = MemoryUsage::default();
+        for mapping in parse_smaps(SMAPS) {
+            total.add(&mapping.usage);
+        }
+        let kb = |n: u64| n * 1024;
+        assert_eq!(total.rss, kb(8 + 12 + 4 + 4));
+        assert_eq!(total.pss, kb(2 + 12 + 4 + 1));
+        assert_eq!(total.uss, kb(4 + 8 + 4));
+        assert_eq!(total.shared_clean, kb(8 + 4));
+        assert_eq!(total.anonymous, kb(12 + 4));
+        assert_eq!(total.swap, kb(16 + 4));
+        assert_eq!(total.swap_pss, kb(16 + 2));
+    }
+
+    #[test]
+    fn counters_before_any_header_are_dropped() {
+        let mappings = parse_smaps("Rss: 4 kB\n7f00-7f10 rw-p 00000000 00:00 0\nRss: 8 kB\nPss: bogus kB\n");
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].usage.rss, 8 * 1024);
+        assert_eq!(mappings[0].usage.pss, 0);
+    }
+}
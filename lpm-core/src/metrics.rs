@@ -0,0 +1,182 @@
+// src/metrics.rs for lpm-core
+//
+// Bounded per-process time series, filled by sample_metrics on each refresh
+// tick of a caller.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::{ProcessManager, ProcessSnapshot};
+
+/// One measurement of a process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    pub timestamp: DateTime<Local>,
+    pub cpu_usage: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    pub virtual_memory: u64,
+    /// Bytes read since the previous refresh.
+    pub read_bytes: u64,
+    /// Bytes written since the previous refresh.
+    pub written_bytes: u64,
+    pub threads: usize,
+}
+
+impl Sample {
+    fn new(p: &ProcessSnapshot, timestamp: DateTime<Local>) -> Self {
+        Self {
+            timestamp,
+            cpu_usage: p.cpu_usage,
+            memory: p.memory,
+            virtual_memory: p.virtual_memory,
+            read_bytes: p.disk_io.read_bytes,
+            written_bytes: p.disk_io.written_bytes,
+            threads: p.threads,
+        }
+    }
+}
+
+/// How much history is kept per process; whichever limit is hit first wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub max_samples: usize,
+    pub max_age: Duration,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self { max_samples: 600, max_age: Duration::from_secs(600) }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Series {
+    /// Start time of the process the samples belong to; a reused pid starts
+    /// a new series.
+    start_time: u64,
+    samples: VecDeque<Sample>,
+}
+
+/// Ring buffers of [`Sample`]s keyed by pid.
+#[derive(Debug, Clone, Default)]
+pub struct MetricStore {
+    retention: Retention,
+    series: HashMap<u32, Series>,
+}
+
+impl MetricStore {
+    pub fn new(retention: Retention) -> Self {
+        Self { retention, series: HashMap::new() }
+    }
+
+    pub fn retention(&self) -> Retention {
+        self.retention
+    }
+
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.retention = retention;
+        for series in self.series.values_mut() {
+            trim(&mut series.samples, retention, Local::now());
+        }
+    }
+
+    /// Appends a sample for each process in a full refresh taken at `now`
+    /// and forgets processes that are no longer listed.
+    pub fn record(&mut self, processes: &[ProcessSnapshot], now: DateTime<Local>) {
+        let mut series = HashMap::with_capacity(processes.len());
+        for p in processes {
+            let mut s = match self.series.remove(&p.pid) {
+                Some(s) if s.start_time == p.start_time => s,
+                _ => Series { start_time: p.start_time, samples: VecDeque::new() },
+            };
+            s.samples.push_back(Sample::new(p, now));
+            trim(&mut s.samples, self.retention, now);
+            series.insert(p.pid, s);
+        }
+        self.series = series;
+    }
+
+    /// Samples of `pid` from the last `window`, oldest first.
+    pub fn history_for(&self, pid: u32, window: Duration) -> Vec<Sample> {
+        let Some(series) = self.series.get(&pid) else {
+            return Vec::new();
+        };
+        let since = chrono::Duration::from_std(window)
+            .ok()
+            .and_then(|w| Local::now().checked_sub_signed(w));
+        series
+            .samples
+            .iter()
+            .filter(|s| since.is_none_or(|t| s.timestamp >= t))
+            .cloned()
+            .collect()
+    }
+}
+
+fn trim(samples: &mut VecDeque<Sample>, retention: Retention, now: DateTime<Local>) {
+    while samples.len() > retention.max_samples {
+        samples.pop_front();
+    }
+    let max_age = chrono::Duration::from_std(retention.max_age).unwrap_or(chrono::Duration::MAX);
+    while samples.front().is_some_and(|s| now - s.timestamp > max_age) {
+        samples.pop_front();
+    }
+}
+
+impl ProcessManager {
+    pub fn metric_retention(&self) -> Retention {
+        self.metrics.retention()
+    }
+
+    pub fn set_metric_retention(&mut self, retention: Retention) {
+        self.metrics.set_retention(retention);
+    }
+
+    /// Refreshes all processes, records one sample for each and returns
+    /// them. Call this once per refresh tick; the spacing of the samples is
+    /// the spacing of the calls.
+    pub fn sample_metrics(&mut self) -> Vec<ProcessSnapshot> {
+        let processes = self.list_processes();
+        self.metrics.record(&processes, Local::now());
+        processes
+    }
+
+    /// The samples recorded for `pid` during the last `window`, oldest first.
+    /// Empty if [`ProcessManager::sample_metrics`] has not seen the process.
+    pub fn history_for(&self, pid: usize, window: Duration) -> Vec<Sample> {
+        self.metrics.history_for(pid as u32, window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::History;
+
+    #[test]
+    fn only_sample_metrics_records() {
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let pid = std::process::id() as usize;
+        let window = Duration::from_secs(60);
+        manager.list_processes();
+        manager.list_processes_by_name("lpm");
+        assert!(manager.history_for(pid, window).is_empty());
+        manager.sample_metrics();
+        manager.sample_metrics();
+        manager.list_processes();
+        assert_eq!(manager.history_for(pid, window).len(), 2);
+    }
+
+    #[test]
+    fn retention_caps_the_series() {
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        manager.set_metric_retention(Retention { max_samples: 2, max_age: Duration::from_secs(60) });
+        for _ in 0..4 {
+            manager.sample_metrics();
+        }
+        assert_eq!(manager.history_for(std::process::id() as usize, Duration::from_secs(60)).len(), 2);
+    }
+}
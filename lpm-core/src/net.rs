@@ -0,0 +1,483 @@
+// src/net.rs for lpm-core
+//
+// Socket tables from /proc/net/{tcp,tcp6,udp,udp6,unix}, matched to the
+// processes that hold each socket through their fd tables.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::{fds, LpmError, ProcessManager};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+impl Protocol {
+    const ALL: [Protocol; 5] = [Protocol::Tcp, Protocol::Tcp6, Protocol::Udp, Protocol::Udp6, Protocol::Unix];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+            Protocol::Unix => "unix",
+        }
+    }
+
+    fn is_tcp(&self) -> bool {
+        matches!(self, Protocol::Tcp | Protocol::Tcp6)
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// Socket state, named as ss(8) shows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    /// A bound UDP socket or an unconnected Unix socket.
+    Unconnected,
+    Unknown,
+}
+
+impl SocketState {
+    fn from_tcp(code: u8) -> Self {
+        match code {
+            0x01 => SocketState::Established,
+            0x02 => SocketState::SynSent,
+            0x03 => SocketState::SynRecv,
+            0x04 => SocketState::FinWait1,
+            0x05 => SocketState::FinWait2,
+            0x06 => SocketState::TimeWait,
+            0x07 => SocketState::Close,
+            0x08 => SocketState::CloseWait,
+            0x09 => SocketState::LastAck,
+            0x0A => SocketState::Listen,
+            0x0B => SocketState::Closing,
+            _ => SocketState::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for SocketState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(match self {
+            SocketState::Established => "ESTAB",
+            SocketState::SynSent => "SYN-SENT",
+            SocketState::SynRecv => "SYN-RECV",
+            SocketState::FinWait1 => "FIN-WAIT-1",
+            SocketState::FinWait2 => "FIN-WAIT-2",
+            SocketState::TimeWait => "TIME-WAIT",
+            SocketState::Close => "CLOSE",
+            SocketState::CloseWait => "CLOSE-WAIT",
+            SocketState::LastAck => "LAST-ACK",
+            SocketState::Listen => "LISTEN",
+            SocketState::Closing => "CLOSING",
+            SocketState::Unconnected => "UNCONN",
+            SocketState::Unknown => "UNKNOWN",
+        })
+    }
+}
+
+/// A socket and the process holding it. A socket shared by several
+/// processes (e.g. after fork) appears once per process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Connection {
+    pub protocol: Protocol,
+    pub state: SocketState,
+    /// Local and remote address of IP sockets.
+    pub local: Option<SocketAddr>,
+    pub remote: Option<SocketAddr>,
+    /// Bound path of Unix sockets, or the name of an abstract one without
+    /// the leading `@`.
+    pub path: Option<String>,
+    /// Whether `path` is a name in the abstract namespace rather than a
+    /// file.
+    #[serde(default)]
+    pub is_abstract: bool,
+    pub inode: u64,
+    /// Owner, if a process whose fds lpm can read holds the socket.
+    pub pid: Option<u32>,
+    pub name: Option<String>,
+}
+
+impl Connection {
+    /// Whether the socket accepts connections or datagrams on a local port:
+    /// a listening TCP socket or a bound, unconnected UDP socket.
+    pub fn is_listening(&self) -> bool {
+        match self.protocol {
+            Protocol::Tcp | Protocol::Tcp6 => self.state == SocketState::Listen,
+            Protocol::Udp | Protocol::Udp6 => self.state == SocketState::Unconnected,
+            Protocol::Unix => self.state == SocketState::Listen,
+        }
+    }
+
+    pub fn local_port(&self) -> Option<u16> {
+        self.local.map(|a| a.port())
+    }
+}
+
+impl fmt::Display for Connection {
+    /// One ss-like line: protocol, state, local and remote end, owner.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let addr = |a: Option<SocketAddr>| a.map_or("*".to_string(), |a| a.to_string());
+        let (local, remote) = match self.protocol {
+            Protocol::Unix => {
+                let path = match &self.path {
+                    Some(path) if self.is_abstract => format!("@{}", path),
+                    Some(path) => path.clone(),
+                    None => "*".to_string(),
+                };
+                (path, "*".to_string())
+            }
+            _ => (addr(self.local), addr(self.remote)),
+        };
+        write!(f, "{:<5} {:<10} {:<40} {:<40}", self.protocol, self.state, local, remote)?;
+        match (self.pid, &self.name) {
+            (Some(pid), Some(name)) => write!(f, " [{}] {}", pid, name),
+            (Some(pid), None) => write!(f, " [{}]", pid),
+            _ => f.write_str(" -"),
+        }
+    }
+}
+
+/// A socket table row before owners are attached.
+#[derive(Clone)]
+struct RawSocket {
+    protocol: Protocol,
+    state: SocketState,
+    local: Option<SocketAddr>,
+    remote: Option<SocketAddr>,
+    path: Option<String>,
+    is_abstract: bool,
+    inode: u64,
+}
+
+/// Parses `ADDR:PORT` as printed in /proc/net/tcp*: the address is the raw
+/// network-order bytes printed as host-order 32-bit words.
+fn parse_address(field: &str) -> Option<SocketAddr> {
+    let (addr, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let word = |i: usize| -> Option<[u8; 4]> {
+        Some(u32::from_str_radix(addr.get(i * 8..i * 8 + 8)?, 16).ok()?.to_ne_bytes())
+    };
+    let ip = match addr.len() {
+        8 => IpAddr::V4(Ipv4Addr::from(word(0)?)),
+        32 => {
+            let mut bytes = [0u8; 16];
+            for i in 0..4 {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&word(i)?);
+            }
+            IpAddr::V6(Ipv6Addr::from(bytes))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+fn parse_inet(protocol: Protocol, content: &str) -> Vec<RawSocket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let local = parse_address(fields.get(1)?)?;
+            let remote = parse_address(fields.get(2)?)?;
+            let code = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+            let state = match (protocol.is_tcp(), code) {
+                (true, _) => SocketState::from_tcp(code),
+                (false, 0x01) => SocketState::Established,
+                (false, 0x07) => SocketState::Unconnected,
+                (false, _) => SocketState::Unknown,
+            };
+            Some(RawSocket {
+                protocol,
+                state,
+                local: Some(local),
+                remote: (!remote.ip().is_unspecified() || remote.port() != 0).then_some(remote),
+                path: None,
+                is_abstract: false,
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// `__SO_ACCEPTCON` in the Flags column: the socket is listening.
+const UNIX_ACCEPTCON: u32 = 0x10000;
+
+/// Parses /proc/net/unix. The path is the rest of the line after the seven
+/// fixed columns and may contain spaces; the inode column is padded, so the
+/// columns are split on runs of whitespace first.
+fn parse_unix(content: &str) -> Vec<RawSocket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = Vec::with_capacity(7);
+            let mut rest = line;
+            for _ in 0..7 {
+                rest = rest.trim_start();
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                fields.push(&rest[..end]);
+                rest = &rest[end..];
+            }
+            let path = rest.strip_prefix(' ').filter(|p| !p.is_empty());
+            let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+            let state = if flags & UNIX_ACCEPTCON != 0 {
+                SocketState::Listen
+            } else if *fields.get(5)? == "03" {
+                SocketState::Established
+            } else {
+                SocketState::Unconnected
+            };
+            Some(RawSocket {
+                protocol: Protocol::Unix,
+                state,
+                local: None,
+                remote: None,
+                path: path.map(|p| p.strip_prefix('@').unwrap_or(p).to_string()),
+                is_abstract: path.is_some_and(|p| p.starts_with('@')),
+                inode: fields.get(6)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Reads every socket table under `dir` (`/proc/net` or `/proc/<pid>/net`,
+/// which shows the process's network namespace). Missing tables, such as
+/// tcp6 without IPv6, are skipped.
+fn read_tables(dir: &str) -> Vec<RawSocket> {
+    let mut sockets = Vec::new();
+    for protocol in Protocol::ALL {
+        let Ok(content) = fs::read_to_string(format!("{}/{}", dir, protocol.as_str())) else {
+            continue;
+        };
+        sockets.extend(match protocol {
+            Protocol::Unix => parse_unix(&content),
+            _ => parse_inet(protocol, &content),
+        });
+    }
+    sockets
+}
+
+/// Inodes of the sockets among `pid`'s open descriptors.
+fn socket_inodes(pid: libc::pid_t) -> std::io::Result<HashSet<u64>> {
+    Ok(fds::fd_numbers(pid)?
+        .into_iter()
+        .filter_map(|fd| {
+            let target = fs::read_link(format!("/proc/{}/fd/{}", pid, fd)).ok()?;
+            target.to_str()?.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect())
+}
+
+fn connection(raw: RawSocket, owner: Option<(u32, String)>) -> Connection {
+    let (pid, name) = owner.map_or((None, None), |(pid, name)| (Some(pid), Some(name)));
+    Connection {
+        protocol: raw.protocol,
+        state: raw.state,
+        local: raw.local,
+        remote: raw.remote,
+        path: raw.path,
+        is_abstract: raw.is_abstract,
+        inode: raw.inode,
+        pid,
+        name,
+    }
+}
+
+impl ProcessManager {
+    /// Sockets held open by `pid`, read from its own network namespace.
+    /// Reading another user's descriptors needs the same privileges as
+    /// ptrace.
+    pub fn connections(&mut self, pid: usize) -> Result<Vec<Connection>, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        let inodes = socket_inodes(raw).map_err(|e| LpmError::from_io(e, pid32))?;
+        let name = self.process_name(pid);
+        Ok(read_tables(&format!("/proc/{}/net", raw))
+            .into_iter()
+            .filter(|s| inodes.contains(&s.inode))
+            .map(|s| connection(s, Some((pid32, name.clone().unwrap_or_default()))))
+            .collect())
+    }
+
+    /// Every socket in lpm's network namespace, with one entry per owning
+    /// process. Sockets whose owner cannot be determined have no pid.
+    pub fn all_connections(&mut self) -> Vec<Connection> {
+        let mut owners: HashMap<u64, Vec<(u32, String)>> = HashMap::new();
+        for p in self.list_processes() {
+            let Ok(inodes) = socket_inodes(p.pid as libc::pid_t) else {
+                continue;
+            };
+            for inode in inodes {
+                owners.entry(inode).or_default().push((p.pid, p.name.clone()));
+            }
+        }
+        let mut connections = Vec::new();
+        for socket in read_tables("/proc/net") {
+            match owners.get(&socket.inode) {
+                Some(holders) => {
+                    for owner in holders {
+                        connections.push(connection(socket.clone(), Some(owner.clone())));
+                    }
+                }
+                None => connections.push(connection(socket, None)),
+            }
+        }
+        connections
+    }
+
+    /// The TCP and UDP sockets listening on `port`, with their owners.
+    pub fn who_listens(&mut self, port: u16) -> Vec<Connection> {
+        self.all_connections()
+            .into_iter()
+            .filter(|c| c.is_listening() && c.local_port() == Some(port))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The kernel prints addresses as host-order words; these fixtures are
+    // what a little-endian machine shows.
+    const TCP: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000   999        0 23456 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0CEA 0100007F:D2F4 01 00000000:00000000 02:0009A2B1 00000000   999        0 34567 2 0000000000000000 20 4 30 10 -1
+";
+
+    const TCP6: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1234 1 0000000000000000 100 0 0 10 0
+   1: B80D0120000000000000000001000200:0016 000080FE000000000000000001000000:C350 01 00000000:00000000 02:00000A2B 00000000     0        0 5678 4 0000000000000000 20 4 1 10 -1
+";
+
+    const UDP: &str = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  371: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 4321 2 0000000000000000 0
+";
+
+    const UNIX: &str = "\
+Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 23456 /run/dbus/system_bus_socket
+0000000000000000: 00000003 00000000 00000000 0001 03 34567 /tmp/dir with spaces/sock
+0000000000000000: 00000003 00000000 00000000 0001 03 45678
+0000000000000000: 00000002 00000000 00010000 0001 01  9876 @/tmp/.X11-unix/X0
+0000000000000000: 00000002 00000000 00000000 0002 01   321
+";
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_ipv4_addresses() {
+        assert_eq!(parse_address("0100007F:0050"), Some("127.0.0.1:80".parse().unwrap()));
+        assert_eq!(parse_address("00000000:0000"), Some("0.0.0.0:0".parse().unwrap()));
+        assert_eq!(parse_address("0101A8C0:01BB"), Some("192.168.1.1:443".parse().unwrap()));
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_ipv6_addresses_word_by_word() {
+        assert_eq!(parse_address("00000000000000000000000001000000:0016"), Some("[::1]:22".parse().unwrap()));
+        assert_eq!(
+            parse_address("B80D0120000000000000000001000200:01BB"),
+            Some("[2001:db8::2:1]:443".parse().unwrap())
+        );
+        assert_eq!(
+            parse_address("0000000000000000FFFF00000100007F:0050"),
+            Some("[::ffff:127.0.0.1]:80".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_addresses() {
+        assert_eq!(parse_address("0100007F"), None);
+        assert_eq!(parse_address("0100007F:XYZ"), None);
+        assert_eq!(parse_address("0100:0050"), None);
+        assert_eq!(parse_address("0100007G:0050"), None);
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_tcp_listen_and_established() {
+        let sockets = parse_inet(Protocol::Tcp, TCP);
+        assert_eq!(sockets.len(), 2);
+        let listen = &sockets[0];
+        assert_eq!(listen.state, SocketState::Listen);
+        assert_eq!(listen.local, Some("127.0.0.1:3306".parse().unwrap()));
+        assert_eq!(listen.remote, None);
+        assert_eq!(listen.inode, 23456);
+        let established = &sockets[1];
+        assert_eq!(established.state, SocketState::Established);
+        assert_eq!(established.remote, Some("127.0.0.1:54004".parse().unwrap()));
+        assert_eq!(established.inode, 34567);
+    }
+
+    #[test]
+    #[cfg(target_endian = "little")]
+    fn parses_tcp6_and_udp() {
+        let sockets = parse_inet(Protocol::Tcp6, TCP6);
+        assert_eq!(sockets[0].state, SocketState::Listen);
+        assert_eq!(sockets[0].local, Some("[::]:22".parse().unwrap()));
+        assert_eq!(sockets[1].local, Some("[2001:db8::2:1]:22".parse().unwrap()));
+        assert_eq!(sockets[1].remote, Some("[fe80::1]:50000".parse().unwrap()));
+
+        let sockets = parse_inet(Protocol::Udp, UDP);
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].state, SocketState::Unconnected);
+        assert_eq!(sockets[0].local, Some("127.0.0.53:53".parse().unwrap()));
+        assert_eq!(sockets[0].inode, 4321);
+    }
+
+    #[test]
+    fn parses_unix_sockets() {
+        let sockets = parse_unix(UNIX);
+        let rows: Vec<_> = sockets.iter().map(|s| (s.state, s.path.as_deref(), s.is_abstract, s.inode)).collect();
+        assert_eq!(
+            rows,
+            [
+                (SocketState::Listen, Some("/run/dbus/system_bus_socket"), false, 23456),
+                (SocketState::Established, Some("/tmp/dir with spaces/sock"), false, 34567),
+                (SocketState::Established, None, false, 45678),
+                (SocketState::Listen, Some("/tmp/.X11-unix/X0"), true, 9876),
+                (SocketState::Unconnected, None, false, 321),
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_abstract_unix_names_with_an_at() {
+        let raw = parse_unix(UNIX).swap_remove(3);
+        let line = connection(raw, Some((42, "Xorg".to_string()))).to_string();
+        assert!(line.starts_with("unix  LISTEN     @/tmp/.X11-unix/X0 "), "{}", line);
+        assert!(line.ends_with(" [42] Xorg"), "{}", line);
+    }
+}
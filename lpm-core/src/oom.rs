@@ -0,0 +1,130 @@
+// src/oom.rs for lpm-core
+//
+// The OOM killer's view of a process: /proc/<pid>/oom_score and the
+// user-controlled bias in /proc/<pid>/oom_score_adj.
+
+use std::fs;
+use std::io;
+
+use crate::{ActionKind, LpmError, ProcessManager};
+
+/// `oom_score_adj` that exempts a process from the OOM killer.
+pub const OOM_SCORE_ADJ_MIN: i32 = -1000;
+/// `oom_score_adj` that makes a process the first one killed.
+pub const OOM_SCORE_ADJ_MAX: i32 = 1000;
+
+fn read_number<T: std::str::FromStr>(pid: libc::pid_t, file: &str) -> io::Result<T> {
+    fs::read_to_string(format!("/proc/{}/{}", pid, file))?
+        .trim()
+        .parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("malformed {}", file)))
+}
+
+/// `oom_score` and `oom_score_adj` of `pid` for listings; `None` if it is
+/// gone.
+pub(crate) fn read_oom(pid: libc::pid_t) -> Option<(u32, i32)> {
+    Some((read_number(pid, "oom_score").ok()?, read_number(pid, "oom_score_adj").ok()?))
+}
+
+impl ProcessManager {
+    /// The badness the OOM killer currently assigns to `pid`, from 0 to
+    /// 2000; the highest is killed first.
+    pub fn oom_score(&mut self, pid: usize) -> Result<u32, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        read_number(raw, "oom_score").map_err(|e| LpmError::from_io(e, pid32))
+    }
+
+    pub fn oom_score_adj(&mut self, pid: usize) -> Result<i32, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        read_number(raw, "oom_score_adj").map_err(|e| LpmError::from_io(e, pid32))
+    }
+
+    /// Sets the OOM score adjustment of `pid`, from [`OOM_SCORE_ADJ_MIN`]
+    /// (never killed) to [`OOM_SCORE_ADJ_MAX`] (killed first). Going below
+    /// the lowest value the process has had needs CAP_SYS_RESOURCE.
+    pub fn set_oom_score_adj(&mut self, pid: usize, value: i32) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let old = self.oom_score_adj(pid).ok();
+        let result = if (OOM_SCORE_ADJ_MIN..=OOM_SCORE_ADJ_MAX).contains(&value) {
+            self.check_target(pid).and_then(|raw| {
+                fs::write(format!("/proc/{}/oom_score_adj", raw), value.to_string())
+                    .map_err(|e| LpmError::from_io(e, pid as u32))
+            })
+        } else {
+            Err(LpmError::InvalidInput(format!(
+                "OOM score adjustment {} is out of range ({}..={})",
+                value, OOM_SCORE_ADJ_MIN, OOM_SCORE_ADJ_MAX
+            )))
+        };
+        let mut params = vec![("adj", value.to_string())];
+        if let Some(old) = old {
+            params.push(("old", old.to_string()));
+        }
+        self.record(ActionKind::OomAdjust, pid, name, &params, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{History, Outcome};
+    use std::process::Command;
+
+    fn manager() -> ProcessManager {
+        ProcessManager::with_history(History::in_memory())
+    }
+
+    #[test]
+    fn rejects_out_of_range_adjustments() {
+        let mut manager = manager();
+        // No such pid, so an error other than InvalidInput would mean /proc
+        // was consulted before the range check.
+        for value in [OOM_SCORE_ADJ_MIN - 1, OOM_SCORE_ADJ_MAX + 1] {
+            let err = manager.set_oom_score_adj(99_999_999, value).unwrap_err();
+            assert!(matches!(err, LpmError::InvalidInput(_)), "{:?}", err);
+            let entry = manager.history.entries().last().unwrap();
+            assert_eq!(entry.action, ActionKind::OomAdjust);
+            assert_eq!(entry.pid, 99_999_999);
+            assert_eq!(entry.params["adj"], value.to_string());
+            assert!(!entry.params.contains_key("old"));
+            assert!(matches!(entry.outcome, Outcome::Failed { .. }));
+        }
+        assert_eq!(manager.history.entries().len(), 2);
+    }
+
+    #[test]
+    fn reads_the_current_process() {
+        let mut manager = manager();
+        let pid = std::process::id() as usize;
+        let score = manager.oom_score(pid).unwrap();
+        assert!(score <= 2000);
+        let adj = manager.oom_score_adj(pid).unwrap();
+        assert!((OOM_SCORE_ADJ_MIN..=OOM_SCORE_ADJ_MAX).contains(&adj));
+        assert_eq!(read_oom(pid as libc::pid_t), Some((score, adj)));
+        assert!(matches!(manager.oom_score(99_999_999), Err(LpmError::NoSuchProcess(99_999_999))));
+    }
+
+    #[test]
+    fn raises_the_adjustment_of_a_child() {
+        // lpm refuses to change its own pid, so work on a child instead.
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id() as usize;
+        let mut manager = manager();
+        let old = manager.oom_score_adj(pid).unwrap();
+        let raised = (old + 1).min(OOM_SCORE_ADJ_MAX);
+        let result = manager.set_oom_score_adj(pid, raised);
+        let adj = manager.oom_score_adj(pid);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        result.unwrap();
+        assert_eq!(adj.unwrap(), raised);
+        let entry = manager.history.entries().last().unwrap();
+        assert_eq!(entry.params["old"], old.to_string());
+        assert_eq!(entry.params["adj"], raised.to_string());
+        assert_eq!(entry.outcome, Outcome::Success);
+    }
+}
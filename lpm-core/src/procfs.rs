@@ -0,0 +1,175 @@
+// src/procfs.rs for lpm-core
+//
+// Small readers for /proc/<pid>/* files that sysinfo does not expose.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+
+/// Fields of `/proc/<pid>/stat` that lpm uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Stat {
+    pub state: char,
+    pub ppid: i32,
+    pub pgrp: i32,
+    pub session: i32,
+    /// Controlling terminal as an encoded device number; 0 for none.
+    pub tty_nr: u32,
+    /// User and system CPU time in clock ticks.
+    pub utime: u64,
+    pub stime: u64,
+    pub nice: i32,
+    pub num_threads: u64,
+    /// Start time in clock ticks after boot; identifies a pid incarnation.
+    pub starttime: u64,
+    /// CPU the task last ran on.
+    pub processor: u32,
+    /// Real-time priority, 0 for non-real-time policies.
+    pub rt_priority: u32,
+    /// Scheduling policy number (SCHED_*).
+    pub policy: i32,
+}
+
+pub(crate) fn read_stat(pid: libc::pid_t) -> io::Result<Stat> {
+    parse_stat(&fs::read_to_string(format!("/proc/{}/stat", pid))?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stat"))
+}
+
+/// `/proc/<pid>/task/<tid>/stat`, the same format for a single thread.
+pub(crate) fn read_task_stat(pid: libc::pid_t, tid: libc::pid_t) -> io::Result<Stat> {
+    parse_stat(&fs::read_to_string(format!("/proc/{}/task/{}/stat", pid, tid))?)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stat"))
+}
+
+/// Thread ids of `pid`, in ascending order.
+pub(crate) fn list_tasks(pid: libc::pid_t) -> io::Result<Vec<libc::pid_t>> {
+    let mut tids: Vec<libc::pid_t> = fs::read_dir(format!("/proc/{}/task", pid))?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    tids.sort_unstable();
+    Ok(tids)
+}
+
+fn parse_stat(content: &str) -> Option<Stat> {
+    // The command name may contain spaces and parentheses, so split after the
+    // last ')'. Fields after it start at field 3 (state).
+    let rest = &content[content.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |n: usize| fields.get(n).copied();
+    Some(Stat {
+        state: field(0)?.chars().next()?,
+        ppid: field(1)?.parse().ok()?,
+        pgrp: field(2)?.parse().ok()?,
+        session: field(3)?.parse().ok()?,
+        tty_nr: field(4)?.parse::<i64>().ok()? as u32,
+        utime: field(11)?.parse().ok()?,
+        stime: field(12)?.parse().ok()?,
+        nice: field(16)?.parse().ok()?,
+        num_threads: field(17)?.parse().ok()?,
+        starttime: field(19)?.parse().ok()?,
+        processor: field(36)?.parse().ok()?,
+        rt_priority: field(37)?.parse().ok()?,
+        policy: field(38)?.parse().ok()?,
+    })
+}
+
+/// Contents of `/proc/<pid>/status`.
+pub(crate) fn read_status(pid: libc::pid_t) -> io::Result<String> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+}
+
+/// The value after `key` (e.g. `"Seccomp:"`) in `status`, trimmed.
+pub(crate) fn status_field<'a>(status: &'a str, key: &str) -> Option<&'a str> {
+    status.lines().find_map(|l| l.strip_prefix(key)).map(str::trim)
+}
+
+/// Whether the process started at `starttime` is still running. Zombies and
+/// pids that have been reused by another process count as exited.
+pub(crate) fn is_running(pid: libc::pid_t, starttime: u64) -> bool {
+    match read_stat(pid) {
+        Ok(stat) => stat.starttime == starttime && !matches!(stat.state, 'Z' | 'X' | 'x'),
+        Err(_) => false,
+    }
+}
+
+/// Reads a NUL-separated file such as `cmdline` or `environ`, keeping
+/// non-UTF-8 bytes intact.
+pub(crate) fn read_nul_separated(pid: libc::pid_t, file: &str) -> io::Result<Vec<OsString>> {
+    let bytes = fs::read(format!("/proc/{}/{}", pid, file))?;
+    // Every entry is NUL-terminated; empty entries (e.g. a "" argument) are kept.
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(&bytes);
+    if bytes.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(bytes
+        .split(|&b| b == 0)
+        .map(|part| OsString::from_vec(part.to_vec()))
+        .collect())
+}
+
+/// Target of a `/proc/<pid>/` symlink such as `cwd` or `exe`, without the
+/// ` (deleted)` marker the kernel appends for unlinked files.
+pub(crate) fn read_link(pid: libc::pid_t, link: &str) -> io::Result<PathBuf> {
+    let target = fs::read_link(format!("/proc/{}/{}", pid, link))?.into_os_string().into_vec();
+    let target = target.strip_suffix(b" (deleted)").map(<[u8]>::to_vec).unwrap_or(target);
+    Ok(PathBuf::from(OsString::from_vec(target)))
+}
+
+/// Real and effective user and group, and supplementary groups, from
+/// `/proc/<pid>/status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Credentials {
+    pub uid: libc::uid_t,
+    pub euid: libc::uid_t,
+    pub gid: libc::gid_t,
+    pub egid: libc::gid_t,
+    pub groups: Vec<libc::gid_t>,
+}
+
+pub(crate) fn read_credentials(pid: libc::pid_t) -> io::Result<Credentials> {
+    let status = read_status(pid)?;
+    let field = |key: &str| -> Option<Vec<u32>> {
+        status_field(&status, key)?.split_whitespace().map(|v| v.parse().ok()).collect()
+    };
+    // Uid: and Gid: list the real, effective, saved and filesystem ids.
+    let nth = |key: &str, n: usize| {
+        field(key)
+            .and_then(|v| v.get(n).copied())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed status"))
+    };
+    Ok(Credentials {
+        uid: nth("Uid:", 0)?,
+        euid: nth("Uid:", 1)?,
+        gid: nth("Gid:", 0)?,
+        egid: nth("Gid:", 1)?,
+        groups: field("Groups:").unwrap_or_default(),
+    })
+}
+
+/// The cgroup v2 path of `pid` from the `0::` line of `/proc/<pid>/cgroup`;
+/// `None` if the process is only in v1 hierarchies.
+pub(crate) fn read_cgroup(pid: libc::pid_t) -> io::Result<Option<String>> {
+    let cgroups = fs::read_to_string(format!("/proc/{}/cgroup", pid))?;
+    Ok(cgroups.lines().find_map(|l| l.strip_prefix("0::")).map(str::to_string))
+}
+
+/// Soft and hard value of a limit in `/proc/<pid>/limits`, looked up by its
+/// description (e.g. `"Max open files"`). `None` means unlimited.
+pub(crate) fn read_limit(pid: libc::pid_t, name: &str) -> io::Result<(Option<u64>, Option<u64>)> {
+    parse_limit(&fs::read_to_string(format!("/proc/{}/limits", pid))?, name)
+}
+
+/// Like [`read_limit`], on the already read contents of the limits file.
+pub(crate) fn parse_limit(limits: &str, name: &str) -> io::Result<(Option<u64>, Option<u64>)> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "malformed limits");
+    let line = limits.lines().find_map(|l| l.strip_prefix(name)).ok_or_else(malformed)?;
+    let value = |v: Option<&str>| match v {
+        Some("unlimited") => Ok(None),
+        Some(v) => v.parse().map(Some).map_err(|_| malformed()),
+        None => Err(malformed()),
+    };
+    let mut fields = line.split_whitespace();
+    Ok((value(fields.next())?, value(fields.next())?))
+}
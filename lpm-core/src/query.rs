@@ -0,0 +1,741 @@
+// src/query.rs for lpm-core
+//
+// A small filter language over process snapshots:
+//
+//     cpu > 50 and user == "postgres" and cmd ~ /--worker/
+//     not (state == stopped or mem >= 1.5G)
+//
+// Comparisons are `field op value`, combined with `and`, `or`, `not` and
+// parentheses. Numeric fields take `== != < <= > >=`; text fields take
+// `== !=` and the regex matches `~ !~`. Only after `~` and `!~` does a
+// slash start a regex, so paths need no quotes:
+//
+//     exe == /usr/bin/bash or cgroup == /user.slice
+
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{resolve_group, LpmError, ProcessManager, ProcessSnapshot};
+
+/// A parsed filter expression.
+#[derive(Debug, Clone)]
+pub struct Query {
+    source: String,
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Query, QueryError> {
+        let tokens = lex(source)?;
+        let mut parser = Parser { tokens, pos: 0, end: source.len() };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(QueryError::new(token.pos, format!("unexpected {}", token.kind)));
+        }
+        Ok(Query { source: source.to_string(), expr })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn matches(&self, p: &ProcessSnapshot) -> bool {
+        self.expr.eval(p)
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Query::parse(s)
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// Why a query did not parse, with the byte offset where it went wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+
+    /// The query with a caret under the offending position, for terminals.
+    pub fn pointer(&self, source: &str) -> String {
+        let column = source[..self.position.min(source.len())].chars().count();
+        format!("{}\n{}^", source, " ".repeat(column))
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (at position {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl From<QueryError> for LpmError {
+    fn from(e: QueryError) -> Self {
+        LpmError::InvalidInput(format!("invalid query: {}", e))
+    }
+}
+
+/// A snapshot field that queries can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Pid,
+    Ppid,
+    Name,
+    /// The command line joined with spaces.
+    Cmd,
+    Exe,
+    /// Real or effective user, by name or uid.
+    User,
+    Uid,
+    Euid,
+    /// Group membership (real, effective or supplementary), by name or gid.
+    Group,
+    Gid,
+    Egid,
+    State,
+    Cpu,
+    /// Resident memory in bytes; values accept K/M/G suffixes.
+    Mem,
+    Vmem,
+    Threads,
+    Read,
+    Write,
+    OomScore,
+    OomScoreAdj,
+    /// The cgroup v2 path, e.g. `/user.slice`.
+    Cgroup,
+}
+
+impl Field {
+    const NAMES: &'static [(&'static str, Field)] = &[
+        ("pid", Field::Pid),
+        ("ppid", Field::Ppid),
+        ("name", Field::Name),
+        ("cmd", Field::Cmd),
+        ("cmdline", Field::Cmd),
+        ("exe", Field::Exe),
+        ("user", Field::User),
+        ("uid", Field::Uid),
+        ("euid", Field::Euid),
+        ("group", Field::Group),
+        ("gid", Field::Gid),
+        ("egid", Field::Egid),
+        ("state", Field::State),
+        ("cpu", Field::Cpu),
+        ("mem", Field::Mem),
+        ("memory", Field::Mem),
+        ("rss", Field::Mem),
+        ("vmem", Field::Vmem),
+        ("threads", Field::Threads),
+        ("read", Field::Read),
+        ("write", Field::Write),
+        ("oom", Field::OomScore),
+        ("oom_score", Field::OomScore),
+        ("oom_adj", Field::OomScoreAdj),
+        ("oom_score_adj", Field::OomScoreAdj),
+        ("cgroup", Field::Cgroup),
+    ];
+
+    fn lookup(name: &str) -> Option<Field> {
+        let name = name.to_lowercase();
+        Field::NAMES.iter().find(|(n, _)| *n == name).map(|&(_, f)| f)
+    }
+
+    fn is_text(&self) -> bool {
+        matches!(self, Field::Name | Field::Cmd | Field::Exe | Field::User | Field::Group | Field::State | Field::Cgroup)
+    }
+
+    fn takes_bytes(&self) -> bool {
+        matches!(self, Field::Mem | Field::Vmem | Field::Read | Field::Write)
+    }
+
+    fn number(&self, p: &ProcessSnapshot) -> Option<f64> {
+        Some(match self {
+            Field::Pid => p.pid as f64,
+            Field::Ppid => p.ppid? as f64,
+            Field::Uid => p.uid? as f64,
+            Field::Euid => p.euid? as f64,
+            Field::Gid => p.gid? as f64,
+            Field::Egid => p.egid? as f64,
+            Field::Cpu => p.cpu_usage as f64,
+            Field::Mem => p.memory as f64,
+            Field::Vmem => p.virtual_memory as f64,
+            Field::Threads => p.threads as f64,
+            Field::Read => p.disk_io.read_bytes as f64,
+            Field::Write => p.disk_io.written_bytes as f64,
+            Field::OomScore => p.oom_score as f64,
+            Field::OomScoreAdj => p.oom_score_adj as f64,
+            _ => return None,
+        })
+    }
+
+    fn text(&self, p: &ProcessSnapshot) -> String {
+        match self {
+            Field::Name => p.name.clone(),
+            Field::Cmd => p.cmdline.join(" "),
+            Field::Exe => p.exe.to_string_lossy().into_owned(),
+            Field::User => p.user_label(),
+            Field::Group => p.group.clone().or_else(|| p.gid.map(|g| g.to_string())).unwrap_or_default(),
+            Field::State => p.state.to_string(),
+            Field::Cgroup => p.cgroup.clone().unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+    NotMatch,
+}
+
+impl Op {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Match => "~",
+            Op::NotMatch => "!~",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Number(Field, Op, f64),
+    Text(Field, Op, String),
+    /// Group membership by gid; resolved when the query is parsed.
+    Group(Op, u32),
+    Regex(Field, bool, Regex),
+}
+
+impl Expr {
+    fn eval(&self, p: &ProcessSnapshot) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(p) && b.eval(p),
+            Expr::Or(a, b) => a.eval(p) || b.eval(p),
+            Expr::Not(e) => !e.eval(p),
+            Expr::Number(field, op, value) => {
+                // A missing value (no parent, unknown uid) matches nothing.
+                let Some(actual) = field.number(p) else { return false };
+                match op {
+                    Op::Eq => actual == *value,
+                    Op::Ne => actual != *value,
+                    Op::Lt => actual < *value,
+                    Op::Le => actual <= *value,
+                    Op::Gt => actual > *value,
+                    Op::Ge => actual >= *value,
+                    Op::Match | Op::NotMatch => false,
+                }
+            }
+            Expr::Text(Field::User, op, value) => p.matches_user(value) == (*op == Op::Eq),
+            Expr::Group(op, gid) => p.in_group(*gid) == (*op == Op::Eq),
+            Expr::Text(field, op, value) => {
+                let actual = field.text(p);
+                let equal = if *field == Field::State {
+                    actual.eq_ignore_ascii_case(value)
+                } else {
+                    actual == *value
+                };
+                equal == (*op == Op::Eq)
+            }
+            Expr::Regex(field, negate, re) => re.is_match(&field.text(p)) != *negate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    /// A number as written, with any unit suffix.
+    Number(String),
+    Str(String),
+    Regex(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Word(w) => write!(f, "'{}'", w),
+            TokenKind::Number(n) => write!(f, "number {}", n),
+            TokenKind::Str(s) => write!(f, "string \"{}\"", s),
+            TokenKind::Regex(r) => write!(f, "regex /{}/", r),
+            TokenKind::Op(op) => write!(f, "'{}'", op.as_str()),
+            TokenKind::And => f.write_str("'and'"),
+            TokenKind::Or => f.write_str("'or'"),
+            TokenKind::Not => f.write_str("'not'"),
+            TokenKind::LParen => f.write_str("'('"),
+            TokenKind::RParen => f.write_str("')'"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    pos: usize,
+}
+
+fn lex(source: &str) -> Result<Vec<Token>, QueryError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        let two = source.get(i..i + 2).unwrap_or("");
+        let kind = match c {
+            _ if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'(' => {
+                i += 1;
+                TokenKind::LParen
+            }
+            b')' => {
+                i += 1;
+                TokenKind::RParen
+            }
+            _ if two == "&&" => {
+                i += 2;
+                TokenKind::And
+            }
+            _ if two == "||" => {
+                i += 2;
+                TokenKind::Or
+            }
+            _ if ["==", "!=", "<=", ">=", "!~"].contains(&two) => {
+                i += 2;
+                TokenKind::Op(match two {
+                    "==" => Op::Eq,
+                    "!=" => Op::Ne,
+                    "<=" => Op::Le,
+                    ">=" => Op::Ge,
+                    _ => Op::NotMatch,
+                })
+            }
+            b'=' | b'<' | b'>' | b'~' => {
+                i += 1;
+                TokenKind::Op(match c {
+                    b'=' => Op::Eq,
+                    b'<' => Op::Lt,
+                    b'>' => Op::Gt,
+                    _ => Op::Match,
+                })
+            }
+            b'!' => {
+                i += 1;
+                TokenKind::Not
+            }
+            b'/' if matches!(tokens.last(), Some(Token { kind: TokenKind::Op(Op::Match | Op::NotMatch), .. })) => {
+                let (text, end) = quoted(source, i)?;
+                i = end;
+                TokenKind::Regex(text)
+            }
+            // Anywhere else a slash starts a path, which runs to the next
+            // space or parenthesis.
+            b'/' => {
+                while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !b"()".contains(&bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::Word(source[start..i].to_string())
+            }
+            b'"' | b'\'' => {
+                let (text, end) = quoted(source, i)?;
+                i = end;
+                TokenKind::Str(text)
+            }
+            // A leading minus, for fields such as oom_adj that go negative.
+            _ if c.is_ascii_digit() || c == b'.' || (c == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) => {
+                i += 1;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.') {
+                    i += 1;
+                }
+                TokenKind::Number(source[start..i].to_string())
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || b"_-.".contains(&bytes[i])) {
+                    i += 1;
+                }
+                match source[start..i].to_lowercase().as_str() {
+                    "and" => TokenKind::And,
+                    "or" => TokenKind::Or,
+                    "not" => TokenKind::Not,
+                    _ => TokenKind::Word(source[start..i].to_string()),
+                }
+            }
+            _ => {
+                let ch = source[i..].chars().next().unwrap_or('?');
+                return Err(QueryError::new(i, format!("unexpected character '{}'", ch)));
+            }
+        };
+        tokens.push(Token { kind, pos: start });
+    }
+    Ok(tokens)
+}
+
+/// Reads a literal delimited by the character at `start`; a backslash
+/// escapes the delimiter. Regexes keep their other escapes for the regex
+/// engine. Returns the contents and the offset after the closing delimiter.
+fn quoted(source: &str, start: usize) -> Result<(String, usize), QueryError> {
+    let delim = source.as_bytes()[start] as char;
+    let mut out = String::new();
+    let mut chars = source[start + 1..].char_indices();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, next)) if next == delim => out.push(next),
+                Some((_, next)) if delim != '/' && next == '\\' => out.push(next),
+                Some((_, next)) => {
+                    out.push('\\');
+                    out.push(next);
+                }
+                None => break,
+            },
+            _ if c == delim => return Ok((out, start + 1 + offset + 1)),
+            _ => out.push(c),
+        }
+    }
+    let what = if delim == '/' { "regex" } else { "string" };
+    Err(QueryError::new(start, format!("unterminated {}", what)))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Offset of the end of the input, for "expected ... at end" errors.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token, QueryError> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => Err(QueryError::new(self.end, format!("expected {} at end of query", expected))),
+        }
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek().is_some_and(|t| t.kind == *kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.and()?;
+        while self.eat(&TokenKind::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.unary()?;
+        while self.eat(&TokenKind::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        if self.eat(&TokenKind::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let token = self.next("a comparison")?;
+        match token.kind {
+            TokenKind::LParen => {
+                let expr = self.or()?;
+                let close = self.next("')'")?;
+                if close.kind != TokenKind::RParen {
+                    return Err(QueryError::new(close.pos, format!("expected ')', found {}", close.kind)));
+                }
+                Ok(expr)
+            }
+            TokenKind::Word(name) => self.comparison(&name, token.pos),
+            other => Err(QueryError::new(token.pos, format!("expected a field name, found {}", other))),
+        }
+    }
+
+    fn comparison(&mut self, name: &str, pos: usize) -> Result<Expr, QueryError> {
+        let field = Field::lookup(name).ok_or_else(|| {
+            let known: Vec<&str> = Field::NAMES.iter().map(|(n, _)| *n).collect();
+            QueryError::new(pos, format!("unknown field '{}' (known: {})", name, known.join(", ")))
+        })?;
+        let op_token = self.next("an operator")?;
+        let TokenKind::Op(op) = op_token.kind else {
+            return Err(QueryError::new(op_token.pos, format!("expected an operator after '{}', found {}", name, op_token.kind)));
+        };
+        let value = self.next("a value")?;
+        let mismatch = |what: &str| {
+            QueryError::new(op_token.pos, format!("'{}' cannot be used with {} field '{}'", op.as_str(), what, name))
+        };
+
+        if matches!(op, Op::Match | Op::NotMatch) {
+            if !field.is_text() {
+                return Err(mismatch("numeric"));
+            }
+            let pattern = match value.kind {
+                TokenKind::Regex(p) | TokenKind::Str(p) => p,
+                other => {
+                    return Err(QueryError::new(value.pos, format!("expected a /regex/ after '{}', found {}", op.as_str(), other)))
+                }
+            };
+            let re = Regex::new(&pattern)
+                .map_err(|e| {
+                    // Syntax errors span several lines and repeat the pattern; keep the reason.
+                    let reason = e.to_string().lines().last().unwrap_or_default().trim_start_matches("error: ").to_string();
+                    QueryError::new(value.pos, format!("invalid regex: {}", reason))
+                })?;
+            return Ok(Expr::Regex(field, op == Op::NotMatch, re));
+        }
+
+        if field.is_text() {
+            if !matches!(op, Op::Eq | Op::Ne) {
+                return Err(mismatch("text"));
+            }
+            let text = match value.kind {
+                TokenKind::Str(s) | TokenKind::Word(s) | TokenKind::Number(s) => s,
+                other => {
+                    return Err(QueryError::new(value.pos, format!("expected a value for '{}', found {}", name, other)))
+                }
+            };
+            if field == Field::Group {
+                let gid = resolve_group(&text)
+                    .ok_or_else(|| QueryError::new(value.pos, format!("unknown group '{}'", text)))?;
+                return Ok(Expr::Group(op, gid));
+            }
+            return Ok(Expr::Text(field, op, text));
+        }
+
+        let TokenKind::Number(text) = value.kind else {
+            return Err(QueryError::new(value.pos, format!("expected a number for '{}', found {}", name, value.kind)));
+        };
+        let number = parse_number(&text, field.takes_bytes())
+            .ok_or_else(|| QueryError::new(value.pos, format!("invalid number '{}'", text)))?;
+        Ok(Expr::Number(field, op, number))
+    }
+}
+
+/// Parses `42`, `1.5` and, for byte fields, sizes like `500M`, `2GiB` or
+/// `64kb` (all powers of 1024).
+fn parse_number(text: &str, bytes: bool) -> Option<f64> {
+    let split = text.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(text.len());
+    let (digits, unit) = text.split_at(split);
+    let value: f64 = digits.parse().ok()?;
+    if unit.is_empty() {
+        return Some(value);
+    }
+    if !bytes {
+        return None;
+    }
+    let power = match unit.to_lowercase().as_str() {
+        "b" => 0,
+        "k" | "kb" | "kib" => 1,
+        "m" | "mb" | "mib" => 2,
+        "g" | "gb" | "gib" => 3,
+        "t" | "tb" | "tib" => 4,
+        _ => return None,
+    };
+    Some(value * 1024f64.powi(power))
+}
+
+impl ProcessManager {
+    /// Lists the processes matching `query`.
+    pub fn query_processes(&mut self, query: &Query) -> Vec<ProcessSnapshot> {
+        self.list_processes().into_iter().filter(|p| query.matches(p)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiskIo, ProcessState};
+
+    fn snapshot() -> ProcessSnapshot {
+        ProcessSnapshot {
+            pid: 4242,
+            ppid: Some(1),
+            name: "postgres".to_string(),
+            cmdline: vec!["postgres: checkpointer".to_string(), "--worker".to_string()],
+            exe: "/usr/bin/postgres".into(),
+            uid: Some(26),
+            user: Some("postgres".to_string()),
+            euid: Some(26),
+            effective_user: Some("postgres".to_string()),
+            gid: Some(26),
+            group: Some("postgres".to_string()),
+            egid: Some(26),
+            groups: vec![26, 100],
+            state: ProcessState::Sleeping,
+            cpu_usage: 75.0,
+            memory: 2 << 30,
+            virtual_memory: 4 << 30,
+            start_time: 0,
+            threads: 8,
+            disk_io: DiskIo::default(),
+            scheduling: Default::default(),
+            io_priority: Default::default(),
+            cgroup: Some("/system.slice/postgresql.service".to_string()),
+            oom_score: 700,
+            oom_score_adj: -500,
+        }
+    }
+
+    fn matches(source: &str) -> bool {
+        Query::parse(source).unwrap().matches(&snapshot())
+    }
+
+    fn error(source: &str) -> QueryError {
+        Query::parse(source).unwrap_err()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert!(matches("pid == 1 and cpu > 50 or name == postgres"));
+        assert!(matches("name == postgres or pid == 1 and cpu > 90"));
+        assert!(!matches("(name == postgres or pid == 1) and cpu > 90"));
+        assert!(!matches("not name == postgres or pid == 1"));
+        assert!(matches("not (pid == 1 or cpu < 50)"));
+        assert!(matches("NOT pid == 1 AND name == postgres"));
+        assert!(matches("pid != 1 && (cpu >= 75 || threads < 2)"));
+        assert!(matches("! state == stopped"));
+    }
+
+    #[test]
+    fn numbers_and_units() {
+        assert!(matches("cpu > 50"));
+        assert!(!matches("cpu > 75.5"));
+        assert!(matches("mem > 1G"));
+        assert!(matches("mem == 2GiB"));
+        assert!(matches("memory >= 2048M"));
+        assert!(!matches("vmem < 512mb"));
+        assert!(matches("threads = 8"));
+        assert!(matches("oom_adj < -100"));
+        assert!(matches("oom >= 700"));
+        assert_eq!(parse_number("64kb", true), Some(65536.0));
+        assert_eq!(parse_number("1.5k", true), Some(1536.0));
+        assert_eq!(parse_number("1G", false), None);
+        assert_eq!(parse_number("1Q", true), None);
+    }
+
+    #[test]
+    fn quoting_and_escapes() {
+        assert!(matches(r#"cmd == "postgres: checkpointer --worker""#));
+        assert!(matches("user == 'postgres'"));
+        assert!(matches("user == 26"));
+        assert!(matches(r#"name != "say \"hi\"""#));
+        assert!(matches(r"name != 'back\\slash'"));
+        assert!(matches("state == SLEEPING"));
+        assert!(matches("group == 100"));
+    }
+
+    #[test]
+    fn paths_need_no_quotes() {
+        assert!(matches("exe == /usr/bin/postgres"));
+        assert!(matches("exe != /usr/bin/bash"));
+        assert!(matches("cgroup == /system.slice/postgresql.service"));
+        assert!(matches("(cgroup != /user.slice)"));
+    }
+
+    #[test]
+    fn regexes() {
+        assert!(matches("cmd ~ /--worker/"));
+        assert!(matches("cmd ~ /^postgres: \\w+/"));
+        assert!(matches(r"exe ~ /\/usr\/bin\//"));
+        assert!(matches("exe ~ \"^/usr\""));
+        assert!(matches("cgroup !~ /user\\.slice/"));
+        assert!(!matches("name ~ /^PG/"));
+        assert!(matches("name ~ /(?i)^POST/"));
+    }
+
+    #[test]
+    fn lexer_errors() {
+        assert_eq!(error("cpu > 50 $"), QueryError::new(9, "unexpected character '$'"));
+        assert_eq!(error("name == \"postgres"), QueryError::new(8, "unterminated string"));
+        assert_eq!(error("cmd ~ /worker"), QueryError::new(6, "unterminated regex"));
+    }
+
+    #[test]
+    fn parser_errors() {
+        assert_eq!(error(""), QueryError::new(0, "expected a comparison at end of query"));
+        assert_eq!(error("cpu"), QueryError::new(3, "expected an operator at end of query"));
+        assert_eq!(error("cpu >"), QueryError::new(5, "expected a value at end of query"));
+        assert_eq!(error("cpu > 1 2"), QueryError::new(8, "unexpected number 2"));
+        assert_eq!(error("(cpu > 1"), QueryError::new(8, "expected ')' at end of query"));
+        assert_eq!(error("(cpu > 1 cpu"), QueryError::new(9, "expected ')', found 'cpu'"));
+        assert_eq!(error("> 5"), QueryError::new(0, "expected a field name, found '>'"));
+        assert!(error("colour == red").message.starts_with("unknown field 'colour' (known: pid, ppid,"));
+        assert_eq!(error("cpu 50"), QueryError::new(4, "expected an operator after 'cpu', found number 50"));
+    }
+
+    #[test]
+    fn type_errors() {
+        assert_eq!(error("cpu ~ /5/"), QueryError::new(4, "'~' cannot be used with numeric field 'cpu'"));
+        assert_eq!(error("name > 5"), QueryError::new(5, "'>' cannot be used with text field 'name'"));
+        assert_eq!(error("name ~ 5"), QueryError::new(7, "expected a /regex/ after '~', found number 5"));
+        assert!(error("name ~ /(/").message.starts_with("invalid regex: "));
+        assert_eq!(error("name == ("), QueryError::new(8, "expected a value for 'name', found '('"));
+        assert_eq!(
+            error("group == no-such-group-here"),
+            QueryError::new(9, "unknown group 'no-such-group-here'")
+        );
+        assert_eq!(error("cpu > \"x\""), QueryError::new(6, "expected a number for 'cpu', found string \"x\""));
+        assert_eq!(error("cpu > 5G"), QueryError::new(6, "invalid number '5G'"));
+        assert_eq!(error("mem > 1Q"), QueryError::new(6, "invalid number '1Q'"));
+    }
+
+    #[test]
+    fn error_display() {
+        let e = error("cpu >> 5");
+        assert_eq!(e.to_string(), "expected a number for 'cpu', found '>' (at position 6)");
+        assert_eq!(e.pointer("cpu >> 5"), "cpu >> 5\n     ^");
+        assert!(matches!(LpmError::from(e), LpmError::InvalidInput(m) if m.starts_with("invalid query: ")));
+    }
+}
@@ -0,0 +1,161 @@
+// src/remediation.rs for lpm-core
+//
+// Actions an alert rule can take on its own when it fires.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::{ActionKind, Alert, LpmError, ProcessManager, Signal};
+
+/// An automatic response to a firing alert, run against the offending
+/// process at most once per rule cooldown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Remediation {
+    Renice { nice: i32 },
+    Signal {
+        #[serde(with = "signal_name")]
+        signal: Signal,
+    },
+    Suspend,
+    /// Runs `command` with `sh -c`, passing the alert as JSON on stdin.
+    Hook { command: String },
+}
+
+impl fmt::Display for Remediation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Remediation::Renice { nice } => write!(f, "renice {}", nice),
+            Remediation::Signal { signal } => write!(f, "signal {}", signal.name()),
+            Remediation::Suspend => f.write_str("suspend"),
+            Remediation::Hook { command } => write!(f, "hook `{}`", command),
+        }
+    }
+}
+
+/// Signals in rule files are written by name (`"TERM"`, `"RTMIN+1"`).
+mod signal_name {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::Signal;
+
+    pub fn serialize<S: Serializer>(signal: &Signal, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&signal.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Signal, D::Error> {
+        String::deserialize(d)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl ProcessManager {
+    /// Runs `action` for `alert`. The history entry it produces is marked as
+    /// triggered by the alert's rule.
+    pub(crate) fn remediate(&mut self, alert: &Alert, action: &Remediation) -> Result<(), LpmError> {
+        self.trigger = Some(alert.rule.clone());
+        let pid = alert.pid as usize;
+        let result = match action {
+            Remediation::Renice { nice } => self.change_priority(pid, *nice),
+            Remediation::Signal { signal } => self.send_signal(pid, *signal),
+            Remediation::Suspend => self.suspend(pid),
+            Remediation::Hook { command } => {
+                let result = run_hook(command, alert);
+                let name = Some(alert.name.clone());
+                self.record(ActionKind::Hook, pid, name, &[("command", command.clone())], &result);
+                result
+            }
+        };
+        self.trigger = None;
+        result
+    }
+}
+
+/// Starts the hook without waiting for it; a background thread feeds it the
+/// alert and reaps it.
+fn run_hook(command: &str, alert: &Alert) -> Result<(), LpmError> {
+    let json = serde_json::to_vec(alert).map_err(|e| LpmError::Io(e.to_string()))?;
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| LpmError::SpawnFailed(e.to_string()))?;
+    let stdin = child.stdin.take();
+    std::thread::spawn(move || {
+        // A hook that ignores its input may exit before reading it.
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(&json);
+        }
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AlertStatus, Metric, Severity};
+    use chrono::Local;
+    use std::time::{Duration, Instant};
+
+    fn from_json(json: &str) -> serde_json::Result<Remediation> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn deserializes_from_rule_files() {
+        assert_eq!(from_json(r#"{"type":"signal","signal":"TERM"}"#).unwrap(), Remediation::Signal { signal: Signal::TERM });
+        assert_eq!(from_json(r#"{"type":"signal","signal":"SIGKILL"}"#).unwrap(), Remediation::Signal { signal: Signal::KILL });
+        assert_eq!(from_json(r#"{"type":"renice","nice":10}"#).unwrap(), Remediation::Renice { nice: 10 });
+        assert_eq!(from_json(r#"{"type":"suspend"}"#).unwrap(), Remediation::Suspend);
+        assert_eq!(
+            from_json(r#"{"type":"hook","command":"logger lpm"}"#).unwrap(),
+            Remediation::Hook { command: "logger lpm".to_string() }
+        );
+        assert!(from_json(r#"{"type":"signal","signal":"FOO"}"#).is_err());
+        assert!(from_json(r#"{"type":"signal","signal":"RTMIN+99"}"#).is_err());
+        assert!(from_json(r#"{"type":"reboot"}"#).is_err());
+    }
+
+    #[test]
+    fn serializes_signals_by_name() {
+        let action = Remediation::Signal { signal: "RTMIN+1".parse().unwrap() };
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json, r#"{"type":"signal","signal":"RTMIN+1"}"#);
+        assert_eq!(from_json(&json).unwrap(), action);
+        assert_eq!(action.to_string(), "signal RTMIN+1");
+    }
+
+    #[test]
+    fn hooks_get_the_alert_on_stdin() {
+        let out = std::env::temp_dir().join(format!("lpm-hook-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&out);
+        let alert = Alert {
+            rule: "high-cpu".to_string(),
+            severity: Severity::Critical,
+            status: AlertStatus::Firing,
+            pid: 4242,
+            start_time: 1,
+            name: "worker".to_string(),
+            user: None,
+            metric: Metric::Cpu,
+            value: 95.0,
+            threshold: 80.0,
+            first_seen: Local::now(),
+            last_seen: Local::now(),
+            actions: Vec::new(),
+        };
+        let command = format!("cat > {0}.tmp && mv {0}.tmp {0}", out.display());
+        run_hook(&command, &alert).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !out.exists() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let received: Alert = serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        assert_eq!(received, alert);
+        std::fs::remove_file(&out).unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+// src/restart.rs for lpm-core
+
+use std::ffi::{OsStr, OsString};
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::procfs::{self, Credentials};
+use crate::{ActionKind, LpmError, ProcessManager, DEFAULT_GRACE};
+
+/// How [`ProcessManager::restart_process`] starts the replacement process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestartOptions {
+    /// Start the new process in its own session (setsid) with stdin from
+    /// /dev/null, so it outlives lpm and its terminal.
+    pub detach: bool,
+    /// Append stdout and stderr to this file. Detached processes without a
+    /// log file write to /dev/null; attached ones inherit lpm's stdio.
+    pub log_file: Option<PathBuf>,
+    /// Grace period for the old process between SIGTERM and SIGKILL.
+    pub grace: Duration,
+}
+
+impl Default for RestartOptions {
+    fn default() -> Self {
+        Self { detach: false, log_file: None, grace: DEFAULT_GRACE }
+    }
+}
+
+/// Everything needed to start a copy of a running process.
+struct LaunchSpec {
+    program: PathBuf,
+    argv: Vec<OsString>,
+    cwd: PathBuf,
+    env: Vec<(OsString, OsString)>,
+    /// Only set when lpm runs as root and can switch to the original user.
+    credentials: Option<Credentials>,
+}
+
+impl LaunchSpec {
+    fn capture(pid: libc::pid_t) -> Result<Self, LpmError> {
+        let pid32 = pid as u32;
+        let io_err = |e| LpmError::from_io(e, pid32);
+
+        let argv = procfs::read_nul_separated(pid, "cmdline").map_err(io_err)?;
+        let Some(arg0) = argv.first() else {
+            return Err(LpmError::SpawnFailed(format!(
+                "process {} has no command line (kernel thread or zombie)",
+                pid
+            )));
+        };
+        let program = procfs::read_link(pid, "exe").unwrap_or_else(|_| PathBuf::from(arg0));
+        let cwd = procfs::read_link(pid, "cwd").map_err(io_err)?;
+        let env = procfs::read_nul_separated(pid, "environ")
+            .map_err(io_err)?
+            .into_iter()
+            .filter_map(|var| {
+                let bytes = var.as_bytes();
+                let eq = bytes.iter().position(|&b| b == b'=')?;
+                Some((
+                    OsStr::from_bytes(&bytes[..eq]).to_os_string(),
+                    OsStr::from_bytes(&bytes[eq + 1..]).to_os_string(),
+                ))
+            })
+            .collect();
+        let credentials = if unsafe { libc::geteuid() } == 0 {
+            Some(procfs::read_credentials(pid).map_err(io_err)?)
+        } else {
+            None
+        };
+
+        Ok(Self { program, argv, cwd, env, credentials })
+    }
+
+    fn spawn(&self, options: &RestartOptions) -> Result<u32, LpmError> {
+        let spawn_err = |e: io::Error| LpmError::SpawnFailed(e.to_string());
+
+        let mut cmd = Command::new(&self.program);
+        cmd.arg0(&self.argv[0])
+            .args(&self.argv[1..])
+            .env_clear()
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .current_dir(&self.cwd);
+
+        if options.detach {
+            cmd.stdin(Stdio::null());
+        }
+        match &options.log_file {
+            Some(path) => {
+                let log = OpenOptions::new().create(true).append(true).open(path).map_err(spawn_err)?;
+                cmd.stdout(log.try_clone().map_err(spawn_err)?).stderr(log);
+            }
+            None if options.detach => {
+                cmd.stdout(Stdio::null()).stderr(Stdio::null());
+            }
+            None => {}
+        }
+
+        let detach = options.detach;
+        let credentials = self.credentials.clone();
+        // Runs in the forked child before exec: only async-signal-safe calls.
+        unsafe {
+            cmd.pre_exec(move || {
+                if detach && libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                if let Some(c) = &credentials {
+                    if libc::setgroups(c.groups.len(), c.groups.as_ptr()) != 0
+                        || libc::setgid(c.gid) != 0
+                        || libc::setuid(c.uid) != 0
+                    {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+
+        let mut child = cmd.spawn().map_err(spawn_err)?;
+        let new_pid = child.id();
+        // Reap the child when it exits so a long-running frontend does not
+        // collect zombies.
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(new_pid)
+    }
+}
+
+impl ProcessManager {
+    /// Stops `pid` (SIGTERM, then SIGKILL after `options.grace`) and starts it
+    /// again with the same executable, argv, working directory and
+    /// environment, and, when lpm runs as root, the same user and groups.
+    ///
+    /// Returns the pid of the new process.
+    pub fn restart_process(&mut self, pid: usize, options: &RestartOptions) -> Result<u32, LpmError> {
+        let name = self.process_name(pid);
+        let spec = self.check_target(pid).and_then(LaunchSpec::capture);
+
+        let result = match &spec {
+            Ok(spec) => self
+                .terminate_inner(pid, options.grace)
+                .and_then(|_| spec.spawn(options)),
+            Err(e) => Err(e.clone()),
+        };
+
+        let mut params = vec![("detach", options.detach.to_string())];
+        if let Ok(spec) = &spec {
+            let argv: Vec<_> = spec.argv.iter().map(|a| a.to_string_lossy()).collect();
+            params.push(("argv", serde_json::to_string(&argv).unwrap_or_default()));
+        }
+        if let Ok(new_pid) = &result {
+            params.push(("new_pid", new_pid.to_string()));
+        }
+        self.record(ActionKind::Restart, pid, name, &params, &result);
+        result
+    }
+}
@@ -0,0 +1,255 @@
+// src/sched.rs for lpm-core
+//
+// Scheduling policy and real-time priority through sched_getscheduler(2)
+// and sched_setscheduler(2).
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{error, procfs, ActionKind, LpmError, ProcessManager};
+
+/// Not exported by libc for every target.
+const SCHED_DEADLINE: i32 = 6;
+
+/// A Linux scheduling policy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedPolicy {
+    /// The default time-sharing policy (SCHED_OTHER, also called normal).
+    #[default]
+    Other,
+    /// Real-time, first in first out.
+    Fifo,
+    /// Real-time, round robin.
+    RoundRobin,
+    /// CPU-bound batch work; treated as always CPU-hungry by the scheduler.
+    Batch,
+    /// Runs only when nothing else wants the CPU.
+    Idle,
+    /// Earliest deadline first. Shown but not settable, since it needs
+    /// runtime/deadline/period parameters.
+    Deadline,
+}
+
+impl SchedPolicy {
+    pub const ALL: &'static [SchedPolicy] = &[
+        SchedPolicy::Other,
+        SchedPolicy::Fifo,
+        SchedPolicy::RoundRobin,
+        SchedPolicy::Batch,
+        SchedPolicy::Idle,
+        SchedPolicy::Deadline,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchedPolicy::Other => "other",
+            SchedPolicy::Fifo => "fifo",
+            SchedPolicy::RoundRobin => "rr",
+            SchedPolicy::Batch => "batch",
+            SchedPolicy::Idle => "idle",
+            SchedPolicy::Deadline => "deadline",
+        }
+    }
+
+    /// Whether the policy is real-time and so takes a priority of 1..=99.
+    pub fn is_realtime(&self) -> bool {
+        matches!(self, SchedPolicy::Fifo | SchedPolicy::RoundRobin)
+    }
+
+    fn as_raw(&self) -> i32 {
+        match self {
+            SchedPolicy::Other => libc::SCHED_OTHER,
+            SchedPolicy::Fifo => libc::SCHED_FIFO,
+            SchedPolicy::RoundRobin => libc::SCHED_RR,
+            SchedPolicy::Batch => libc::SCHED_BATCH,
+            SchedPolicy::Idle => libc::SCHED_IDLE,
+            SchedPolicy::Deadline => SCHED_DEADLINE,
+        }
+    }
+
+    /// Maps a policy number as returned by sched_getscheduler or shown in
+    /// `/proc/<pid>/stat`, ignoring SCHED_RESET_ON_FORK.
+    pub(crate) fn from_raw(raw: i32) -> Option<Self> {
+        let raw = raw & !libc::SCHED_RESET_ON_FORK;
+        SchedPolicy::ALL.iter().copied().find(|p| p.as_raw() == raw)
+    }
+}
+
+impl fmt::Display for SchedPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for SchedPolicy {
+    type Err = LpmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        let s = s.strip_prefix("sched_").unwrap_or(&s);
+        match s {
+            "normal" => Ok(SchedPolicy::Other),
+            "roundrobin" | "round_robin" => Ok(SchedPolicy::RoundRobin),
+            _ => SchedPolicy::ALL
+                .iter()
+                .copied()
+                .find(|p| p.as_str() == s)
+                .ok_or_else(|| LpmError::InvalidInput(format!("unknown scheduling policy '{}'", s))),
+        }
+    }
+}
+
+/// Scheduling policy and real-time priority of a process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scheduling {
+    pub policy: SchedPolicy,
+    /// 1..=99 for real-time policies, 0 otherwise.
+    pub priority: u32,
+}
+
+impl fmt::Display for Scheduling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.policy.is_realtime() {
+            write!(f, "{}:{}", self.policy, self.priority)
+        } else {
+            write!(f, "{}", self.policy)
+        }
+    }
+}
+
+fn read_scheduling(pid: libc::pid_t) -> Result<Scheduling, LpmError> {
+    let raw = unsafe { libc::sched_getscheduler(pid) };
+    if raw < 0 {
+        return Err(LpmError::last_os_error(pid as u32));
+    }
+    let mut param = libc::sched_param { sched_priority: 0 };
+    error::check_os(unsafe { libc::sched_getparam(pid, &mut param) }, pid as u32)?;
+    Ok(Scheduling {
+        policy: SchedPolicy::from_raw(raw).unwrap_or_default(),
+        priority: param.sched_priority.max(0) as u32,
+    })
+}
+
+fn write_scheduling(tid: libc::pid_t, sched: Scheduling) -> Result<(), LpmError> {
+    let param = libc::sched_param { sched_priority: sched.priority as i32 };
+    error::check_os(unsafe { libc::sched_setscheduler(tid, sched.policy.as_raw(), &param) }, tid as u32)
+}
+
+fn validate(sched: Scheduling) -> Result<(), LpmError> {
+    if sched.policy == SchedPolicy::Deadline {
+        return Err(LpmError::InvalidInput("SCHED_DEADLINE cannot be set by lpm".to_string()));
+    }
+    let valid = if sched.policy.is_realtime() { 1..=99 } else { 0..=0 };
+    if !valid.contains(&sched.priority) {
+        return Err(LpmError::InvalidInput(format!(
+            "priority {} is invalid for {} (expected {}..={})",
+            sched.priority,
+            sched.policy,
+            valid.start(),
+            valid.end()
+        )));
+    }
+    Ok(())
+}
+
+impl ProcessManager {
+    /// The scheduling policy and real-time priority of the main thread of
+    /// `pid`.
+    pub fn get_scheduling(&mut self, pid: usize) -> Result<Scheduling, LpmError> {
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid as u32))?;
+        read_scheduling(raw)
+    }
+
+    /// Sets the policy and real-time priority of every thread of `pid`.
+    /// Real-time policies need a priority in 1..=99 and usually
+    /// CAP_SYS_NICE; the others need 0.
+    pub fn set_scheduling(&mut self, pid: usize, policy: SchedPolicy, priority: u32) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let old = self.get_scheduling(pid).ok();
+        let sched = Scheduling { policy, priority };
+        let result = validate(sched).and_then(|()| self.check_target(pid)).and_then(|raw| {
+            write_scheduling(raw, sched)?;
+            // Like affinity, the policy is per thread.
+            let tids = procfs::list_tasks(raw).map_err(|e| LpmError::from_io(e, pid as u32))?;
+            for tid in tids.into_iter().filter(|&tid| tid != raw) {
+                match write_scheduling(tid, sched) {
+                    Ok(()) | Err(LpmError::NoSuchProcess(_)) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        });
+        let mut params = vec![("policy", policy.to_string()), ("priority", priority.to_string())];
+        if let Some(old) = old {
+            params.push(("old", old.to_string()));
+        }
+        self.record(ActionKind::Schedule, pid, name, &params, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(policy: SchedPolicy, priority: u32) -> bool {
+        validate(Scheduling { policy, priority }).is_ok()
+    }
+
+    #[test]
+    fn realtime_priorities_are_1_to_99() {
+        for policy in [SchedPolicy::Fifo, SchedPolicy::RoundRobin] {
+            assert!(!check(policy, 0));
+            assert!(check(policy, 1));
+            assert!(check(policy, 99));
+            assert!(!check(policy, 100));
+        }
+    }
+
+    #[test]
+    fn other_policies_take_no_priority() {
+        for policy in [SchedPolicy::Other, SchedPolicy::Batch, SchedPolicy::Idle] {
+            assert!(check(policy, 0));
+            assert!(!check(policy, 1));
+        }
+        for priority in [0, 1, 99] {
+            assert!(!check(SchedPolicy::Deadline, priority));
+        }
+    }
+
+    #[test]
+    fn parses_policy_names() {
+        let parse = |s: &str| s.parse::<SchedPolicy>().unwrap();
+        assert_eq!(parse("SCHED_RR"), SchedPolicy::RoundRobin);
+        assert_eq!(parse("sched_rr"), SchedPolicy::RoundRobin);
+        assert_eq!(parse("round_robin"), SchedPolicy::RoundRobin);
+        assert_eq!(parse("roundrobin"), SchedPolicy::RoundRobin);
+        assert_eq!(parse("normal"), SchedPolicy::Other);
+        assert_eq!(parse("SCHED_OTHER"), SchedPolicy::Other);
+        assert_eq!(parse("Fifo"), SchedPolicy::Fifo);
+        for &policy in SchedPolicy::ALL {
+            assert_eq!(parse(policy.as_str()), policy);
+        }
+        assert!(matches!("sched_fair".parse::<SchedPolicy>(), Err(LpmError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn from_raw_ignores_reset_on_fork() {
+        assert_eq!(SchedPolicy::from_raw(libc::SCHED_OTHER), Some(SchedPolicy::Other));
+        assert_eq!(SchedPolicy::from_raw(libc::SCHED_RR | libc::SCHED_RESET_ON_FORK), Some(SchedPolicy::RoundRobin));
+        assert_eq!(SchedPolicy::from_raw(libc::SCHED_FIFO | libc::SCHED_RESET_ON_FORK), Some(SchedPolicy::Fifo));
+        assert_eq!(SchedPolicy::from_raw(SCHED_DEADLINE), Some(SchedPolicy::Deadline));
+        assert_eq!(SchedPolicy::from_raw(4), None);
+        for &policy in SchedPolicy::ALL {
+            assert_eq!(SchedPolicy::from_raw(policy.as_raw()), Some(policy));
+        }
+    }
+
+    #[test]
+    fn displays_priorities_of_realtime_policies() {
+        assert_eq!(Scheduling { policy: SchedPolicy::Fifo, priority: 50 }.to_string(), "fifo:50");
+        assert_eq!(Scheduling::default().to_string(), "other");
+    }
+}
@@ -0,0 +1,205 @@
+// src/signal.rs for lpm-core
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::LpmError;
+
+/// A Linux signal number, including the real-time range `SIGRTMIN..=SIGRTMAX`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Signal(i32);
+
+const STANDARD: &[(&str, i32)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("STKFLT", libc::SIGSTKFLT),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("IO", libc::SIGIO),
+    ("PWR", libc::SIGPWR),
+    ("SYS", libc::SIGSYS),
+];
+
+impl Signal {
+    pub const HUP: Signal = Signal(libc::SIGHUP);
+    pub const INT: Signal = Signal(libc::SIGINT);
+    pub const KILL: Signal = Signal(libc::SIGKILL);
+    pub const USR1: Signal = Signal(libc::SIGUSR1);
+    pub const USR2: Signal = Signal(libc::SIGUSR2);
+    pub const TERM: Signal = Signal(libc::SIGTERM);
+    pub const CONT: Signal = Signal(libc::SIGCONT);
+    pub const STOP: Signal = Signal(libc::SIGSTOP);
+
+    /// Validates a raw signal number: a standard signal or one in
+    /// `SIGRTMIN..=SIGRTMAX`. The numbers glibc reserves below `SIGRTMIN`
+    /// for its own threading are rejected.
+    pub fn from_raw(signo: i32) -> Result<Self, LpmError> {
+        let standard = STANDARD.iter().any(|&(_, n)| n == signo);
+        if standard || (libc::SIGRTMIN()..=libc::SIGRTMAX()).contains(&signo) {
+            Ok(Signal(signo))
+        } else {
+            Err(LpmError::InvalidSignal(signo.to_string()))
+        }
+    }
+
+    pub fn as_raw(self) -> i32 {
+        self.0
+    }
+
+    /// Every deliverable signal: the standard ones followed by the real-time range.
+    pub fn all() -> Vec<Signal> {
+        STANDARD
+            .iter()
+            .map(|&(_, signo)| Signal(signo))
+            .chain((libc::SIGRTMIN()..=libc::SIGRTMAX()).map(Signal))
+            .collect()
+    }
+
+    /// Name without the `SIG` prefix, e.g. `TERM` or `RTMIN+3`.
+    pub fn name(self) -> String {
+        if let Some((name, _)) = STANDARD.iter().find(|&&(_, signo)| signo == self.0) {
+            return name.to_string();
+        }
+        let (min, max) = (libc::SIGRTMIN(), libc::SIGRTMAX());
+        match self.0 {
+            n if n == min => "RTMIN".to_string(),
+            n if n == max => "RTMAX".to_string(),
+            n if n > min && n - min <= (max - min) / 2 => format!("RTMIN+{}", n - min),
+            n if n > min && n < max => format!("RTMAX-{}", max - n),
+            n => n.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SIG{}", self.name())
+    }
+}
+
+impl FromStr for Signal {
+    type Err = LpmError;
+
+    /// Accepts `TERM`, `SIGTERM`, `term`, `15`, `RTMIN`, `RTMIN+2` and `RTMAX-1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || LpmError::InvalidSignal(s.to_string());
+        let upper = s.trim().to_uppercase();
+        if let Ok(signo) = upper.parse::<i32>() {
+            return Signal::from_raw(signo);
+        }
+        let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+        if let Some(&(_, signo)) = STANDARD.iter().find(|&&(n, _)| n == name) {
+            return Ok(Signal(signo));
+        }
+
+        let (min, max) = (libc::SIGRTMIN(), libc::SIGRTMAX());
+        let offset = |rest: &str| -> Result<i32, LpmError> {
+            if rest.is_empty() {
+                Ok(0)
+            } else {
+                rest[1..].parse::<i32>().map_err(|_| invalid())
+            }
+        };
+        let signo = if let Some(rest) = name.strip_prefix("RTMIN") {
+            if !(rest.is_empty() || rest.starts_with('+')) {
+                return Err(invalid());
+            }
+            min + offset(rest)?
+        } else if let Some(rest) = name.strip_prefix("RTMAX") {
+            if !(rest.is_empty() || rest.starts_with('-')) {
+                return Err(invalid());
+            }
+            max - offset(rest)?
+        } else {
+            return Err(invalid());
+        };
+        if (min..=max).contains(&signo) {
+            Ok(Signal(signo))
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Signal, LpmError> {
+        s.parse()
+    }
+
+    #[test]
+    fn parses_standard_names_and_numbers() {
+        for s in ["TERM", "SIGTERM", "term", "SigTerm", " 15 "] {
+            assert_eq!(parse(s).unwrap(), Signal::TERM, "{}", s);
+        }
+        assert_eq!(parse("kill").unwrap(), Signal::KILL);
+        assert_eq!(parse("SIGCONT").unwrap(), Signal::CONT);
+    }
+
+    #[test]
+    fn parses_real_time_signals() {
+        let (min, max) = (libc::SIGRTMIN(), libc::SIGRTMAX());
+        assert_eq!(parse("RTMIN").unwrap().as_raw(), min);
+        assert_eq!(parse("SIGRTMIN+2").unwrap().as_raw(), min + 2);
+        assert_eq!(parse("rtmax-1").unwrap().as_raw(), max - 1);
+        assert_eq!(parse("RTMAX").unwrap().as_raw(), max);
+        assert_eq!(parse(&min.to_string()).unwrap().name(), "RTMIN");
+    }
+
+    #[test]
+    fn rejects_invalid_signals() {
+        for s in ["RTMIN+99", "RTMIN-1", "RTMAX+1", "RTMIN+", "RTMINX", "0", "-9", "65", "FOO", "SIG", ""] {
+            assert!(matches!(parse(s), Err(LpmError::InvalidSignal(_))), "{}", s);
+        }
+    }
+
+    #[test]
+    fn from_raw_skips_the_glibc_reserved_range() {
+        assert_eq!(Signal::from_raw(libc::SIGTERM).unwrap(), Signal::TERM);
+        for signo in [0, 32, 33, libc::SIGRTMIN() - 1, libc::SIGRTMAX() + 1] {
+            assert!(Signal::from_raw(signo).is_err(), "{}", signo);
+        }
+        assert!(Signal::from_raw(libc::SIGRTMIN()).is_ok());
+        assert!(Signal::from_raw(libc::SIGRTMAX()).is_ok());
+        assert!(parse("32").is_err());
+    }
+
+    #[test]
+    fn names_round_trip() {
+        let all = Signal::all();
+        assert_eq!(all.len(), STANDARD.len() + (libc::SIGRTMAX() - libc::SIGRTMIN() + 1) as usize);
+        for signal in all {
+            let name = signal.name();
+            assert!(name.parse::<i32>().is_err(), "{} has no name", signal.as_raw());
+            assert_eq!(parse(&name).unwrap(), signal);
+            assert_eq!(parse(&signal.to_string()).unwrap(), signal);
+            assert_eq!(Signal::from_raw(signal.as_raw()).unwrap(), signal);
+        }
+    }
+}
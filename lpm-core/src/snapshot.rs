@@ -0,0 +1,267 @@
+// src/snapshot.rs for lpm-core
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use sysinfo::{PidExt, Process, ProcessExt, ProcessStatus};
+
+use crate::ioprio::read_io_priority;
+use crate::oom::read_oom;
+use crate::{procfs, IoPriority, SchedPolicy, Scheduling};
+use crate::users::NameCache;
+
+/// Owned copy of everything lpm knows about a process at one refresh.
+///
+/// Frontends and exporters work on this instead of `sysinfo::Process`, so they
+/// neither borrow the `ProcessManager` nor depend on the sysinfo version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    pub pid: u32,
+    pub ppid: Option<u32>,
+    pub name: String,
+    pub cmdline: Vec<String>,
+    pub exe: PathBuf,
+    /// Real uid.
+    pub uid: Option<u32>,
+    /// Name of the real user.
+    pub user: Option<String>,
+    /// Effective uid, which differs from `uid` for setuid programs.
+    #[serde(default)]
+    pub euid: Option<u32>,
+    #[serde(default)]
+    pub effective_user: Option<String>,
+    /// Real gid.
+    #[serde(default)]
+    pub gid: Option<u32>,
+    /// Name of the real group.
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub egid: Option<u32>,
+    /// Supplementary groups.
+    #[serde(default)]
+    pub groups: Vec<u32>,
+    pub state: ProcessState,
+    /// CPU usage in percent of one core since the previous refresh.
+    pub cpu_usage: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+    /// Virtual memory in bytes.
+    pub virtual_memory: u64,
+    /// Start time in seconds since the Unix epoch.
+    pub start_time: u64,
+    pub threads: usize,
+    pub disk_io: DiskIo,
+    #[serde(default)]
+    pub scheduling: Scheduling,
+    #[serde(default)]
+    pub io_priority: IoPriority,
+    /// cgroup v2 path, e.g. `/user.slice/user-1000.slice`.
+    #[serde(default)]
+    pub cgroup: Option<String>,
+    /// The OOM killer's badness score, 0 to 2000; highest is killed first.
+    #[serde(default)]
+    pub oom_score: u32,
+    /// User-set bias of `oom_score`, -1000 (never killed) to 1000.
+    #[serde(default)]
+    pub oom_score_adj: i32,
+}
+
+/// Disk I/O counters of a process, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskIo {
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    pub total_read_bytes: u64,
+    pub total_written_bytes: u64,
+}
+
+/// Scheduler state of a process, as reported in `/proc/<pid>/stat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessState {
+    Running,
+    Sleeping,
+    DiskSleep,
+    Stopped,
+    Tracing,
+    Zombie,
+    Dead,
+    Idle,
+    Parked,
+    Waking,
+    Wakekill,
+    LockBlocked,
+    Unknown,
+}
+
+impl From<ProcessStatus> for ProcessState {
+    fn from(status: ProcessStatus) -> Self {
+        match status {
+            ProcessStatus::Run => ProcessState::Running,
+            ProcessStatus::Sleep => ProcessState::Sleeping,
+            ProcessStatus::UninterruptibleDiskSleep => ProcessState::DiskSleep,
+            ProcessStatus::Stop => ProcessState::Stopped,
+            ProcessStatus::Tracing => ProcessState::Tracing,
+            ProcessStatus::Zombie => ProcessState::Zombie,
+            ProcessStatus::Dead => ProcessState::Dead,
+            ProcessStatus::Idle => ProcessState::Idle,
+            ProcessStatus::Parked => ProcessState::Parked,
+            ProcessStatus::Waking => ProcessState::Waking,
+            ProcessStatus::Wakekill => ProcessState::Wakekill,
+            ProcessStatus::LockBlocked => ProcessState::LockBlocked,
+            ProcessStatus::Unknown(_) => ProcessState::Unknown,
+        }
+    }
+}
+
+impl ProcessState {
+    /// Maps the state letter of `/proc/<pid>/stat`.
+    pub(crate) fn from_stat(state: char) -> Self {
+        match state {
+            'R' => ProcessState::Running,
+            'S' => ProcessState::Sleeping,
+            'D' => ProcessState::DiskSleep,
+            'T' => ProcessState::Stopped,
+            't' => ProcessState::Tracing,
+            'Z' => ProcessState::Zombie,
+            'X' | 'x' => ProcessState::Dead,
+            'I' => ProcessState::Idle,
+            'P' => ProcessState::Parked,
+            'W' => ProcessState::Waking,
+            'K' => ProcessState::Wakekill,
+            _ => ProcessState::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for ProcessState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ProcessState::Running => "running",
+            ProcessState::Sleeping => "sleeping",
+            ProcessState::DiskSleep => "disk-sleep",
+            ProcessState::Stopped => "stopped",
+            ProcessState::Tracing => "tracing",
+            ProcessState::Zombie => "zombie",
+            ProcessState::Dead => "dead",
+            ProcessState::Idle => "idle",
+            ProcessState::Parked => "parked",
+            ProcessState::Waking => "waking",
+            ProcessState::Wakekill => "wakekill",
+            ProcessState::LockBlocked => "lock-blocked",
+            ProcessState::Unknown => "unknown",
+        })
+    }
+}
+
+impl ProcessSnapshot {
+    pub(crate) fn capture(names: &mut NameCache, p: &Process) -> Self {
+        let raw = p.pid().as_u32() as libc::pid_t;
+        let creds = procfs::read_credentials(raw).ok();
+        let stat = procfs::read_stat(raw).ok();
+        let (oom_score, oom_score_adj) = read_oom(raw).unwrap_or_default();
+        // sysinfo only knows the effective ids; use them if status is gone.
+        let uid = creds.as_ref().map(|c| c.uid).or_else(|| p.user_id().map(|u| **u));
+        let euid = creds.as_ref().map(|c| c.euid).or_else(|| p.user_id().map(|u| **u));
+        let gid = creds.as_ref().map(|c| c.gid).or_else(|| p.group_id().map(|g| *g));
+        let egid = creds.as_ref().map(|c| c.egid).or_else(|| p.group_id().map(|g| *g));
+        let disk = p.disk_usage();
+
+        Self {
+            pid: p.pid().as_u32(),
+            ppid: p.parent().map(|pp| pp.as_u32()),
+            name: p.name().to_string(),
+            cmdline: p.cmd().to_vec(),
+            exe: p.exe().to_path_buf(),
+            uid,
+            user: uid.and_then(|u| names.user(u)),
+            euid,
+            effective_user: euid.and_then(|u| names.user(u)),
+            gid,
+            group: gid.and_then(|g| names.group(g)),
+            egid,
+            groups: creds.map(|c| c.groups).unwrap_or_default(),
+            state: p.status().into(),
+            cpu_usage: p.cpu_usage(),
+            memory: p.memory(),
+            virtual_memory: p.virtual_memory(),
+            start_time: p.start_time(),
+            threads: p.tasks.len().max(1),
+            disk_io: DiskIo {
+                read_bytes: disk.read_bytes,
+                written_bytes: disk.written_bytes,
+                total_read_bytes: disk.total_read_bytes,
+                total_written_bytes: disk.total_written_bytes,
+            },
+            scheduling: stat
+                .map(|s| Scheduling {
+                    policy: SchedPolicy::from_raw(s.policy).unwrap_or_default(),
+                    priority: s.rt_priority,
+                })
+                .unwrap_or_default(),
+            io_priority: read_io_priority(raw).unwrap_or_default(),
+            cgroup: procfs::read_cgroup(raw).ok().flatten(),
+            oom_score,
+            oom_score_adj,
+        }
+    }
+
+    /// Whether `user` (a name or a numeric uid) is the real or effective
+    /// user of the process.
+    pub fn matches_user(&self, user: &str) -> bool {
+        let is = |id: Option<u32>, name: &Option<String>| {
+            name.as_deref() == Some(user) || id.is_some_and(|id| id.to_string() == user)
+        };
+        is(self.uid, &self.user) || is(self.euid, &self.effective_user)
+    }
+
+    /// Whether the process has `gid` as its real, effective or a
+    /// supplementary group.
+    pub fn in_group(&self, gid: u32) -> bool {
+        self.gid == Some(gid) || self.egid == Some(gid) || self.groups.contains(&gid)
+    }
+
+    /// User name if it could be resolved, otherwise the numeric uid.
+    pub fn user_label(&self) -> String {
+        match (&self.user, self.uid) {
+            (Some(name), _) => name.clone(),
+            (None, Some(uid)) => uid.to_string(),
+            (None, None) => "-".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl ProcessSnapshot {
+    /// A sleeping process owned by uid 1000 with everything else zeroed, for
+    /// tests to adjust.
+    pub(crate) fn fixture(pid: u32, name: &str) -> Self {
+        ProcessSnapshot {
+            pid,
+            ppid: Some(1),
+            name: name.to_string(),
+            cmdline: vec![format!("/usr/bin/{}", name)],
+            exe: PathBuf::from(format!("/usr/bin/{}", name)),
+            uid: Some(1000),
+            user: Some("alice".to_string()),
+            euid: Some(1000),
+            effective_user: Some("alice".to_string()),
+            gid: Some(1000),
+            group: Some("alice".to_string()),
+            egid: Some(1000),
+            groups: Vec::new(),
+            state: ProcessState::Sleeping,
+            cpu_usage: 0.0,
+            memory: 0,
+            virtual_memory: 0,
+            start_time: 0,
+            threads: 1,
+            disk_io: DiskIo::default(),
+            scheduling: Scheduling::default(),
+            io_priority: IoPriority::default(),
+            cgroup: None,
+            oom_score: 0,
+            oom_score_adj: 0,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+// src/suspend.rs for lpm-core
+
+use crate::{ActionKind, LpmError, PidResult, ProcessManager, Signal, TreeOrder};
+
+impl ProcessManager {
+    /// Pauses `pid` with SIGSTOP.
+    pub fn suspend(&mut self, pid: usize) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = self.deliver(pid, Signal::STOP);
+        self.record(ActionKind::Suspend, pid, name, &[], &result);
+        result
+    }
+
+    /// Continues `pid` with SIGCONT.
+    pub fn resume(&mut self, pid: usize) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = self.deliver(pid, Signal::CONT);
+        self.record(ActionKind::Resume, pid, name, &[], &result);
+        result
+    }
+
+    /// Pauses `root` and its descendants, parents first so a supervisor is
+    /// stopped before it can react to its children stopping.
+    pub fn suspend_tree(&mut self, root: usize) -> Result<Vec<PidResult>, LpmError> {
+        self.signal_tree_as(ActionKind::Suspend, root, Signal::STOP, TreeOrder::RootFirst)
+    }
+
+    /// Continues `root` and its descendants, children first so they are
+    /// running again by the time their parent resumes.
+    pub fn resume_tree(&mut self, root: usize) -> Result<Vec<PidResult>, LpmError> {
+        self.signal_tree_as(ActionKind::Resume, root, Signal::CONT, TreeOrder::LeavesFirst)
+    }
+}
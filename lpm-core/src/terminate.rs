@@ -0,0 +1,152 @@
+// src/terminate.rs for lpm-core
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{error, procfs, ActionKind, LpmError, ProcessManager, Signal};
+
+/// Grace period used by the frontends when none is given.
+pub const DEFAULT_GRACE: Duration = Duration::from_secs(5);
+
+/// How long to wait for a process to disappear after SIGKILL.
+const KILL_WAIT: Duration = Duration::from_secs(1);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Which signal actually ended a process in [`ProcessManager::terminate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermStage {
+    /// The process exited within the grace period after SIGTERM.
+    Terminated,
+    /// The grace period ran out and SIGKILL was needed.
+    Killed,
+}
+
+impl fmt::Display for TermStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            TermStage::Terminated => "SIGTERM",
+            TermStage::Killed => "SIGKILL",
+        })
+    }
+}
+
+impl ProcessManager {
+    /// Asks `pid` to exit with SIGTERM and escalates to SIGKILL if it is
+    /// still running after `grace`.
+    pub fn terminate(&mut self, pid: usize, grace: Duration) -> Result<TermStage, LpmError> {
+        let name = self.process_name(pid);
+        let result = self.terminate_inner(pid, grace);
+        let mut params = vec![("grace", format!("{}ms", grace.as_millis()))];
+        if let Ok(stage) = &result {
+            params.push(("ended_by", stage.to_string()));
+        }
+        self.record(ActionKind::Terminate, pid, name, &params, &result);
+        result
+    }
+
+    pub(crate) fn terminate_inner(&mut self, pid: usize, grace: Duration) -> Result<TermStage, LpmError> {
+        let raw = self.check_target(pid)?;
+        let pid32 = pid as u32;
+        let starttime = procfs::read_stat(raw)
+            .map_err(|e| LpmError::from_io(e, pid32))?
+            .starttime;
+
+        error::check_os(unsafe { libc::kill(raw, libc::SIGTERM) }, pid32)?;
+        // A stopped process only acts on SIGTERM once it is continued.
+        unsafe { libc::kill(raw, libc::SIGCONT) };
+        if wait_for_exit(raw, starttime, grace) {
+            return Ok(TermStage::Terminated);
+        }
+
+        match error::check_os(unsafe { libc::kill(raw, Signal::KILL.as_raw()) }, pid32) {
+            // It exited between the last poll and the kill.
+            Err(LpmError::NoSuchProcess(_)) => return Ok(TermStage::Terminated),
+            other => other?,
+        }
+        if wait_for_exit(raw, starttime, KILL_WAIT) {
+            Ok(TermStage::Killed)
+        } else {
+            Err(LpmError::StillRunning(pid32))
+        }
+    }
+}
+
+/// Polls until the process is gone or `timeout` elapses; true if it exited.
+pub(crate) fn wait_for_exit(pid: libc::pid_t, starttime: u64, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !procfs::is_running(pid, starttime) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::History;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Child, Command};
+
+    /// Spawns `sh -c script` and waits until it has exec'd into `sleep`, so
+    /// that any trap it sets up is in place.
+    fn spawn_sleeper(script: &str) -> Child {
+        let child = Command::new("sh").arg("-c").arg(script).spawn().unwrap();
+        let comm = format!("/proc/{}/comm", child.id());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while std::fs::read_to_string(&comm).unwrap_or_default().trim() != "sleep" {
+            assert!(Instant::now() < deadline, "child never started sleeping");
+            thread::sleep(Duration::from_millis(10));
+        }
+        child
+    }
+
+    /// The child is a zombie killed by `signal` and disappears once reaped.
+    fn assert_gone(child: &mut Child, signal: i32) {
+        let pid = child.id() as libc::pid_t;
+        assert_eq!(procfs::read_stat(pid).unwrap().state, 'Z');
+        assert_eq!(child.wait().unwrap().signal(), Some(signal));
+        assert!(procfs::read_stat(pid).is_err());
+    }
+
+    #[test]
+    fn sigterm_is_enough_for_a_cooperative_process() {
+        let mut child = spawn_sleeper("exec sleep 30");
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let stage = manager.terminate(child.id() as usize, Duration::from_secs(5)).unwrap();
+        assert_eq!(stage, TermStage::Terminated);
+        assert_gone(&mut child, libc::SIGTERM);
+
+        let entry = manager.history.entries().last().unwrap();
+        assert_eq!(entry.params["ended_by"], "SIGTERM");
+    }
+
+    #[test]
+    fn sigkill_follows_an_ignored_sigterm() {
+        let mut child = spawn_sleeper("trap '' TERM; exec sleep 30");
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let started = Instant::now();
+        let stage = manager.terminate(child.id() as usize, Duration::from_millis(300)).unwrap();
+        assert_eq!(stage, TermStage::Killed);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_gone(&mut child, libc::SIGKILL);
+
+        let entry = manager.history.entries().last().unwrap();
+        assert_eq!(entry.params["ended_by"], "SIGKILL");
+    }
+
+    #[test]
+    fn refuses_protected_processes() {
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let own = std::process::id();
+        assert!(matches!(manager.terminate(1, DEFAULT_GRACE), Err(LpmError::ProtectedProcess(1))));
+        assert!(matches!(manager.terminate(own as usize, DEFAULT_GRACE), Err(LpmError::ProtectedProcess(p)) if p == own));
+    }
+}
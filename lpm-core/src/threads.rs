@@ -0,0 +1,191 @@
+// src/threads.rs for lpm-core
+//
+// Per-thread view of a process from /proc/<pid>/task, and nice/affinity
+// changes for single threads.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::time::Instant;
+use sysinfo::{Pid, SystemExt};
+
+use crate::affinity::{format_cpu_list, read_affinity, write_affinity};
+use crate::{error, procfs, ActionKind, LpmError, ProcessManager, ProcessState};
+
+/// One thread of a process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub state: ProcessState,
+    /// Percent of one CPU used since the previous listing of the same
+    /// process; 0 on the first listing.
+    pub cpu_usage: f32,
+    /// CPU the thread last ran on.
+    pub last_cpu: u32,
+    pub nice: i32,
+    /// CPUs the thread may run on; empty if they could not be read.
+    pub affinity: Vec<usize>,
+}
+
+/// CPU ticks per thread at the previous listing of each process, to turn
+/// cumulative times into usage. Processes that have exited are dropped on
+/// the next listing.
+#[derive(Debug, Default)]
+pub(crate) struct ThreadTimes {
+    last: HashMap<u32, Listing>,
+}
+
+#[derive(Debug)]
+struct Listing {
+    taken: Instant,
+    /// tid -> (starttime, utime + stime).
+    ticks: HashMap<u32, (u64, u64)>,
+}
+
+impl ThreadTimes {
+    /// Records the ticks in `stats` for `pid` and returns each thread's usage
+    /// since the previous call for the same process.
+    fn usage(&mut self, pid: u32, stats: &[(u32, procfs::Stat)]) -> Vec<f32> {
+        let now = Instant::now();
+        let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+        let previous = self.last.get(&pid);
+        let usage = stats
+            .iter()
+            .map(|(tid, s)| match previous.and_then(|l| Some((l.taken, l.ticks.get(tid)?))) {
+                // A reused tid is a different thread.
+                Some((taken, &(start, ticks))) if start == s.starttime => {
+                    let elapsed = now.duration_since(taken).as_secs_f64();
+                    let used = (s.utime + s.stime).saturating_sub(ticks) as f64 / ticks_per_sec;
+                    if elapsed > 0.0 { (used / elapsed * 100.0) as f32 } else { 0.0 }
+                }
+                _ => 0.0,
+            })
+            .collect();
+        let ticks = stats.iter().map(|(tid, s)| (*tid, (s.starttime, s.utime + s.stime))).collect();
+        self.last.insert(pid, Listing { taken: now, ticks });
+        usage
+    }
+
+    /// Forgets the listings of processes for which `alive` is false.
+    fn retain(&mut self, alive: impl Fn(u32) -> bool) {
+        self.last.retain(|&pid, _| alive(pid));
+    }
+}
+
+impl ProcessManager {
+    /// Lists the threads of `pid`. CPU usage is measured since the previous
+    /// call for the same process, so callers that want it should call twice.
+    pub fn threads(&mut self, pid: usize) -> Result<Vec<ThreadInfo>, LpmError> {
+        let pid32 = pid as u32;
+        let raw = libc::pid_t::try_from(pid).map_err(|_| LpmError::NoSuchProcess(pid32))?;
+        let tids = procfs::list_tasks(raw).map_err(|e| LpmError::from_io(e, pid32))?;
+        // Threads can exit between listing the directory and reading them.
+        let stats: Vec<(u32, procfs::Stat)> = tids
+            .iter()
+            .filter_map(|&tid| Some((tid as u32, procfs::read_task_stat(raw, tid).ok()?)))
+            .collect();
+        let processes = self.system.processes();
+        self.thread_times.retain(|p| p == pid32 || processes.contains_key(&Pid::from(p as usize)));
+        let usage = self.thread_times.usage(pid32, &stats);
+
+        Ok(stats
+            .into_iter()
+            .zip(usage)
+            .map(|((tid, stat), cpu_usage)| ThreadInfo {
+                tid,
+                name: fs::read_to_string(format!("/proc/{}/task/{}/comm", raw, tid))
+                    .map(|s| s.trim_end().to_string())
+                    .unwrap_or_default(),
+                state: ProcessState::from_stat(stat.state),
+                cpu_usage,
+                last_cpu: stat.processor,
+                nice: stat.nice,
+                affinity: read_affinity(tid as libc::pid_t).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Sets the nice value of a single thread of `pid`.
+    pub fn set_thread_nice(&mut self, pid: usize, tid: u32, nice: i32) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = if (-20..=19).contains(&nice) {
+            self.check_thread(pid, tid).and_then(|raw| {
+                error::check_os(unsafe { libc::setpriority(libc::PRIO_PROCESS, raw as libc::id_t, nice) }, tid)
+            })
+        } else {
+            Err(LpmError::InvalidNice(nice))
+        };
+        let params = [("tid", tid.to_string()), ("nice", nice.to_string())];
+        self.record(ActionKind::Renice, pid, name, &params, &result);
+        result
+    }
+
+    /// Restricts a single thread of `pid` to `cpus`.
+    pub fn set_thread_affinity(&mut self, pid: usize, tid: u32, cpus: &[usize]) -> Result<(), LpmError> {
+        let name = self.process_name(pid);
+        let result = self.check_thread(pid, tid).and_then(|raw| write_affinity(raw, cpus));
+        let params = [("tid", tid.to_string()), ("cpus", format_cpu_list(cpus))];
+        self.record(ActionKind::Affinity, pid, name, &params, &result);
+        result
+    }
+
+    /// Applies the usual protections to `pid` and checks that `tid` is one of
+    /// its threads.
+    fn check_thread(&mut self, pid: usize, tid: u32) -> Result<libc::pid_t, LpmError> {
+        let raw_pid = self.check_target(pid)?;
+        let raw_tid = libc::pid_t::try_from(tid).map_err(|_| LpmError::NoSuchProcess(tid))?;
+        procfs::read_task_stat(raw_pid, raw_tid).map_err(|e| LpmError::from_io(e, tid))?;
+        Ok(raw_tid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    fn stat(starttime: u64, utime: u64, stime: u64) -> procfs::Stat {
+        procfs::Stat {
+            state: 'R',
+            ppid: 1,
+            pgrp: 100,
+            session: 100,
+            tty_nr: 0,
+            utime,
+            stime,
+            nice: 0,
+            num_threads: 2,
+            starttime,
+            processor: 0,
+            rt_priority: 0,
+            policy: 0,
+        }
+    }
+
+    #[test]
+    fn usage_is_measured_between_listings() {
+        let mut times = ThreadTimes::default();
+        assert_eq!(times.usage(100, &[(100, stat(50, 10, 5)), (101, stat(60, 0, 0))]), vec![0.0, 0.0]);
+        sleep(Duration::from_millis(20));
+        // 101 exited and its tid went to a new thread with a later starttime.
+        let usage = times.usage(100, &[(100, stat(50, 20, 10)), (101, stat(90, 40, 0)), (102, stat(95, 5, 0))]);
+        assert!(usage[0] > 0.0, "{:?}", usage);
+        assert_eq!(&usage[1..], &[0.0, 0.0]);
+
+        // Listings are kept per process.
+        assert_eq!(times.usage(200, &[(100, stat(50, 40, 20))]), vec![0.0]);
+    }
+
+    #[test]
+    fn retain_drops_exited_processes() {
+        let mut times = ThreadTimes::default();
+        times.usage(100, &[(100, stat(50, 10, 0))]);
+        times.usage(200, &[(200, stat(70, 10, 0))]);
+        times.retain(|pid| pid == 200);
+        sleep(Duration::from_millis(20));
+        assert_eq!(times.usage(100, &[(100, stat(50, 90, 0))]), vec![0.0]);
+        assert!(times.usage(200, &[(200, stat(70, 90, 0))])[0] > 0.0);
+    }
+}
@@ -0,0 +1,198 @@
+// src/tree.rs for lpm-core
+
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use sysinfo::{Pid, ProcessExt, SystemExt};
+
+use crate::{procfs, ActionKind, LpmError, ProcessManager, Signal};
+
+/// Outcome of an action on one process of a subtree.
+pub type PidResult = (usize, Result<(), LpmError>);
+
+/// Order in which [`ProcessManager::signal_tree`] visits a subtree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeOrder {
+    /// Children before their parents, so nothing is reparented to init mid-walk.
+    LeavesFirst,
+    /// Parents before their children, so a supervisor cannot respawn them.
+    RootFirst,
+}
+
+impl FromStr for TreeOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "leaves" | "leaves-first" => Ok(TreeOrder::LeavesFirst),
+            "root" | "root-first" => Ok(TreeOrder::RootFirst),
+            _ => Err(format!("unknown tree order '{}' (expected leaves or root)", s)),
+        }
+    }
+}
+
+impl ProcessManager {
+    /// Pids of `root` and all its descendants, parents before children.
+    pub fn descendants(&mut self, root: usize) -> Vec<usize> {
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (pid, _, ppid) in self.get_process_tree() {
+            if let Some(ppid) = ppid {
+                children.entry(ppid).or_default().push(pid);
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut queue = VecDeque::from([root]);
+        while let Some(pid) = queue.pop_front() {
+            order.push(pid);
+            if let Some(kids) = children.get_mut(&pid) {
+                kids.sort_unstable();
+                queue.extend(kids.iter().copied());
+            }
+        }
+        order
+    }
+
+    /// Sends `signal` to `root` and every descendant, one history entry per
+    /// process.
+    ///
+    /// The subtree is captured up front together with each process's start
+    /// time; a pid whose start time has changed by the time it is signalled
+    /// has been reused and is skipped with [`LpmError::NoSuchProcess`].
+    pub fn signal_tree(
+        &mut self,
+        root: usize,
+        signal: Signal,
+        order: TreeOrder,
+    ) -> Result<Vec<PidResult>, LpmError> {
+        self.signal_tree_as(ActionKind::Signal, root, signal, order)
+    }
+
+    /// [`signal_tree`](Self::signal_tree), recording each delivery as `action`.
+    pub(crate) fn signal_tree_as(
+        &mut self,
+        action: ActionKind,
+        root: usize,
+        signal: Signal,
+        order: TreeOrder,
+    ) -> Result<Vec<PidResult>, LpmError> {
+        self.check_target(root)?;
+
+        let mut targets: Vec<(usize, Option<u64>)> = self
+            .descendants(root)
+            .into_iter()
+            .map(|pid| (pid, procfs::read_stat(pid as libc::pid_t).ok().map(|s| s.starttime)))
+            .collect();
+        if order == TreeOrder::LeavesFirst {
+            targets.reverse();
+        }
+
+        let mut results = Vec::with_capacity(targets.len());
+        for (pid, starttime) in targets {
+            let name = self
+                .system
+                .process(Pid::from(pid))
+                .map(|p| p.name().to_string());
+            let result = match starttime {
+                Some(start) if procfs::is_running(pid as libc::pid_t, start) => self.deliver(pid, signal),
+                _ => Err(LpmError::NoSuchProcess(pid as u32)),
+            };
+            let params = [("signal", signal.name()), ("tree_root", root.to_string())];
+            self.record(action, pid, name, &params, &result);
+            results.push((pid, result));
+        }
+        Ok(results)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::History;
+    use std::process::{Child, Command, Stdio};
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    /// A shell with two sleeping children. Once they die it blocks on the
+    /// piped stdin, so it is still there when signalled leaves first.
+    fn spawn_tree() -> Child {
+        Command::new("sh")
+            .arg("-c")
+            .arg("sleep 30 & sleep 30 & wait; read line")
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap()
+    }
+
+    /// The subtree of `root` once both of its children show up.
+    fn wait_for_tree(manager: &mut ProcessManager, root: usize) -> Vec<usize> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let tree = manager.descendants(root);
+            if tree.len() == 3 || Instant::now() > deadline {
+                return tree;
+            }
+            sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn kills_the_whole_tree_leaves_first() {
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let mut child = spawn_tree();
+        let root = child.id() as usize;
+        let tree = wait_for_tree(&mut manager, root);
+        assert_eq!(tree.len(), 3, "{:?}", tree);
+        assert_eq!(tree[0], root);
+        assert!(tree[1] < tree[2]);
+        let starts: Vec<u64> = tree.iter().map(|&pid| procfs::read_stat(pid as libc::pid_t).unwrap().starttime).collect();
+
+        let results = manager.signal_tree(root, Signal::KILL, TreeOrder::LeavesFirst).unwrap();
+        child.wait().unwrap();
+
+        let visited: Vec<usize> = results.iter().map(|(pid, _)| *pid).collect();
+        assert_eq!(visited, tree.iter().rev().copied().collect::<Vec<_>>());
+        for (pid, result) in &results {
+            assert!(result.is_ok(), "{}: {:?}", pid, result);
+        }
+        // The sleeps are reparented and reaped elsewhere; until then they
+        // are zombies, which is_running treats as gone.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while tree.iter().zip(&starts).any(|(&pid, &start)| procfs::is_running(pid as libc::pid_t, start)) {
+            assert!(Instant::now() < deadline, "tree still running");
+            sleep(Duration::from_millis(20));
+        }
+
+        let entries = manager.history.entries();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| e.params["tree_root"] == root.to_string() && e.params["signal"] == Signal::KILL.name()));
+    }
+
+    #[test]
+    fn root_first_signals_the_root_first() {
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let mut child = spawn_tree();
+        let root = child.id() as usize;
+        let tree = wait_for_tree(&mut manager, root);
+        let results = manager.signal_tree(root, Signal::KILL, TreeOrder::RootFirst).unwrap();
+        child.wait().unwrap();
+        let visited: Vec<usize> = results.iter().map(|(pid, _)| *pid).collect();
+        assert_eq!(visited, tree);
+    }
+
+    #[test]
+    fn refuses_protected_roots() {
+        let mut manager = ProcessManager::with_history(History::in_memory());
+        let own = std::process::id() as usize;
+        assert!(manager.signal_tree(own, Signal::KILL, TreeOrder::LeavesFirst).is_err());
+        assert!(manager.signal_tree(1, Signal::KILL, TreeOrder::LeavesFirst).is_err());
+        assert!(manager.history.entries().is_empty());
+    }
+
+    #[test]
+    fn parses_orders() {
+        assert_eq!("leaves".parse::<TreeOrder>(), Ok(TreeOrder::LeavesFirst));
+        assert_eq!("Root-First".parse::<TreeOrder>(), Ok(TreeOrder::RootFirst));
+        assert!("depth".parse::<TreeOrder>().is_err());
+    }
+}
@@ -0,0 +1,113 @@
+// src/users.rs for lpm-core
+//
+// uid/gid <-> name resolution through the C library, so NSS sources such as
+// LDAP work the same way they do for ps(1).
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::mem::MaybeUninit;
+
+/// Calls a `get*_r` function, growing the string buffer while it reports
+/// ERANGE. `call` returns the error number and whether an entry was found.
+fn with_buffer<T>(mut call: impl FnMut(&mut [libc::c_char]) -> (i32, Option<T>)) -> Option<T> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        match call(&mut buf) {
+            (libc::ERANGE, _) if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            (0, found) => return found,
+            _ => return None,
+        }
+    }
+}
+
+fn lookup_passwd(
+    get: impl Fn(*mut libc::passwd, &mut [libc::c_char], *mut *mut libc::passwd) -> i32,
+) -> Option<(u32, String)> {
+    with_buffer(|buf| {
+        let mut pwd = MaybeUninit::<libc::passwd>::uninit();
+        let mut result = std::ptr::null_mut();
+        let err = get(pwd.as_mut_ptr(), buf, &mut result);
+        if err != 0 || result.is_null() {
+            return (err, None);
+        }
+        // Safety: a non-null result points at `pwd`, filled in by libc.
+        let pwd = unsafe { pwd.assume_init() };
+        let name = unsafe { CStr::from_ptr(pwd.pw_name) }.to_string_lossy().into_owned();
+        (0, Some((pwd.pw_uid, name)))
+    })
+}
+
+fn lookup_group(
+    get: impl Fn(*mut libc::group, &mut [libc::c_char], *mut *mut libc::group) -> i32,
+) -> Option<(u32, String)> {
+    with_buffer(|buf| {
+        let mut grp = MaybeUninit::<libc::group>::uninit();
+        let mut result = std::ptr::null_mut();
+        let err = get(grp.as_mut_ptr(), buf, &mut result);
+        if err != 0 || result.is_null() {
+            return (err, None);
+        }
+        // Safety: a non-null result points at `grp`, filled in by libc.
+        let grp = unsafe { grp.assume_init() };
+        let name = unsafe { CStr::from_ptr(grp.gr_name) }.to_string_lossy().into_owned();
+        (0, Some((grp.gr_gid, name)))
+    })
+}
+
+/// The name of user `uid`, if it has one.
+pub fn user_name(uid: u32) -> Option<String> {
+    lookup_passwd(|pwd, buf, result| unsafe {
+        libc::getpwuid_r(uid, pwd, buf.as_mut_ptr(), buf.len(), result)
+    })
+    .map(|(_, name)| name)
+}
+
+/// The name of group `gid`, if it has one.
+pub fn group_name(gid: u32) -> Option<String> {
+    lookup_group(|grp, buf, result| unsafe {
+        libc::getgrgid_r(gid, grp, buf.as_mut_ptr(), buf.len(), result)
+    })
+    .map(|(_, name)| name)
+}
+
+/// Resolves a user name or a numeric uid to a uid.
+pub fn resolve_user(user: &str) -> Option<u32> {
+    if let Ok(uid) = user.parse() {
+        return Some(uid);
+    }
+    let name = CString::new(user).ok()?;
+    lookup_passwd(|pwd, buf, result| unsafe {
+        libc::getpwnam_r(name.as_ptr(), pwd, buf.as_mut_ptr(), buf.len(), result)
+    })
+    .map(|(uid, _)| uid)
+}
+
+/// Resolves a group name or a numeric gid to a gid.
+pub fn resolve_group(group: &str) -> Option<u32> {
+    if let Ok(gid) = group.parse() {
+        return Some(gid);
+    }
+    let name = CString::new(group).ok()?;
+    lookup_group(|grp, buf, result| unsafe {
+        libc::getgrnam_r(name.as_ptr(), grp, buf.as_mut_ptr(), buf.len(), result)
+    })
+    .map(|(gid, _)| gid)
+}
+
+/// Caches id-to-name lookups across refreshes; every process would otherwise
+/// cost a passwd and a group lookup.
+#[derive(Debug, Default)]
+pub(crate) struct NameCache {
+    users: HashMap<u32, Option<String>>,
+    groups: HashMap<u32, Option<String>>,
+}
+
+impl NameCache {
+    pub fn user(&mut self, uid: u32) -> Option<String> {
+        self.users.entry(uid).or_insert_with(|| user_name(uid)).clone()
+    }
+
+    pub fn group(&mut self, gid: u32) -> Option<String> {
+        self.groups.entry(gid).or_insert_with(|| group_name(gid)).clone()
+    }
+}
@@ -11,6 +11,7 @@ mod tabs {
     pub mod alerts_tab;
     pub mod history_tab;
     pub mod graph_tab; // ✅ Added
+    pub mod cgroup_tab;
 }
 
 use tabs::process_tab::build_process_tab;
@@ -19,6 +20,7 @@ use tabs::process_tree_tab::build_process_tree_tab;
 use tabs::alerts_tab::build_alerts_tab;
 use tabs::history_tab::build_history_tab;
 use tabs::graph_tab::build_graph_tab; // ✅ Added
+use tabs::cgroup_tab::build_cgroup_tab;
 
 fn main() {
     let app = Application::builder()
@@ -47,7 +49,8 @@ fn main() {
         let tree_tab = build_process_tree_tab(Rc::clone(&manager));
         let alerts_tab = build_alerts_tab(Rc::clone(&manager));
         let history_tab = build_history_tab(Rc::clone(&manager));
-        let graph_tab = build_graph_tab(); // ✅ Graphs Tab
+        let graph_tab = build_graph_tab(Rc::clone(&manager)); // ✅ Graphs Tab
+        let cgroup_tab = build_cgroup_tab(Rc::clone(&manager));
 
         notebook.append_page(&process_tab, Some(&Label::new(Some("Processes"))));
         notebook.append_page(&performance_tab, Some(&Label::new(Some("Performance"))));
@@ -55,6 +58,7 @@ fn main() {
         notebook.append_page(&alerts_tab, Some(&Label::new(Some("Alerts"))));
         notebook.append_page(&history_tab, Some(&Label::new(Some("History"))));
         notebook.append_page(&graph_tab, Some(&Label::new(Some("Graphs")))); // ✅ Graphs tab visible
+        notebook.append_page(&cgroup_tab, Some(&Label::new(Some("Cgroups"))));
 
         window.set_child(Some(&notebook));
         window.show();
@@ -1,15 +1,24 @@
 // src/tabs/alerts_tab.rs
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Button, Label, ScrolledWindow, TextView, Orientation};
+use gtk::{Box as GtkBox, Button, Entry, Label, ScrolledWindow, TextView, Orientation};
 use std::cell::RefCell;
 use std::rc::Rc;
-use lpm_core::ProcessManager;
-use gtk::glib::clone;
+use std::time::Duration;
+use lpm_core::{Alert, AlertRule, ProcessManager};
+use gtk::glib::{self, clone};
 
 pub fn build_alerts_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
     let vbox = GtkBox::new(Orientation::Vertical, 10);
 
-    let label = Label::new(Some("Check for high CPU or memory usage"));
+    let label = Label::new(Some("Alerts fire once a rule has held for its whole duration"));
+    let rules_label = Label::new(None);
+    rules_label.set_xalign(0.0);
+    rules_label.set_text(&describe_rules(manager.borrow().alert_rules()));
+
+    let rules_entry = Entry::new();
+    rules_entry.set_placeholder_text(Some("Path to a JSON rules file"));
+    rules_entry.set_hexpand(true);
+    let load_button = Button::with_label("Load Rules");
     let check_button = Button::with_label("Run Alert Check");
     let alert_view = TextView::new();
     alert_view.set_editable(false);
@@ -21,22 +30,69 @@ pub fn build_alerts_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
         .child(&alert_view)
         .build();
 
-    check_button.connect_clicked(clone!(@strong manager, @strong alert_view => move |_| {
-        let mut mgr = manager.borrow_mut();
-        let alerts = mgr.check_alerts(80.0, 500_000); // CPU > 80%, Mem > 500MB (in KB)
+    let refresh = Rc::new(clone!(@strong manager, @strong alert_view => move || {
+        // The timer may fire while another tab holds the manager.
+        let Ok(mut mgr) = manager.try_borrow_mut() else { return };
+        let alerts = mgr.check_alerts();
+        alert_view.buffer().set_text(&describe_alerts(&alerts));
+    }));
 
-        let buffer = alert_view.buffer(); // ✅ no need to unwrap or check Option
-        if alerts.is_empty() {
-            buffer.set_text("No alerts. All processes are within normal resource usage.");
-        } else {
-            let output = format!("Alerts detected:\n{}", alerts.join("\n"));
-            buffer.set_text(&output);
+    load_button.connect_clicked(clone!(@strong manager, @strong rules_entry, @strong rules_label, @strong alert_view => move |_| {
+        let path = rules_entry.text();
+        match AlertRule::load(path.as_str()) {
+            Ok(rules) => {
+                manager.borrow_mut().set_alert_rules(rules);
+                rules_label.set_text(&describe_rules(manager.borrow().alert_rules()));
+                alert_view.buffer().set_text("Rules loaded; waiting for the next refresh.");
+            }
+            Err(e) => alert_view.buffer().set_text(&format!("⚠️ {}", e)),
         }
     }));
 
+    check_button.connect_clicked(clone!(@strong refresh => move |_| refresh()));
+
+    // Rules need repeated samples to tell a sustained hog from a spike.
+    glib::timeout_add_local(Duration::from_secs(2), move || {
+        refresh();
+        glib::Continue(true)
+    });
+
+    let rules_box = GtkBox::new(Orientation::Horizontal, 5);
+    rules_box.append(&rules_entry);
+    rules_box.append(&load_button);
+
     vbox.append(&label);
+    vbox.append(&rules_label);
+    vbox.append(&rules_box);
     vbox.append(&check_button);
     vbox.append(&scroll);
     vbox
 }
 
+fn describe_rules(rules: &[AlertRule]) -> String {
+    let lines: Vec<String> = rules
+        .iter()
+        .map(|r| format!(
+            "{} ({}): {} {} {} for {}s",
+            r.name,
+            r.severity,
+            r.metric,
+            r.comparison,
+            r.metric.format(r.threshold),
+            r.duration.as_secs_f64()
+        ))
+        .collect();
+    format!("Rules:\n{}", lines.join("\n"))
+}
+
+fn describe_alerts(alerts: &[Alert]) -> String {
+    if alerts.is_empty() {
+        "No alerts. All processes are within normal resource usage.".to_string()
+    } else {
+        let lines: Vec<String> = alerts
+            .iter()
+            .map(|a| format!("{} (last seen {})", a, a.last_seen.format("%H:%M:%S")))
+            .collect();
+        format!("Alerts detected:\n{}", lines.join("\n"))
+    }
+}
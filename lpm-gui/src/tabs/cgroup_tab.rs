@@ -0,0 +1,129 @@
+// src/tabs/cgroup_tab.rs
+use gtk::prelude::*;
+use gtk::{Box as GtkBox, Button, ComboBoxText, Entry, Label, Orientation, ScrolledWindow, TextView};
+use std::cell::RefCell;
+use std::rc::Rc;
+use lpm_core::{CgroupLimit, ProcessManager};
+use gtk::glib::clone;
+
+pub fn build_cgroup_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+
+    let root_entry = Entry::builder().placeholder_text("cgroup v2 root...").build();
+    root_entry.set_text(&manager.borrow().cgroup_root().to_string_lossy());
+    let path_entry = Entry::builder().placeholder_text("cgroup path, e.g. /lpm/hogs...").build();
+    let pid_entry = Entry::builder().placeholder_text("PID...").build();
+    let show_button = Button::with_label("Show");
+    let create_button = Button::with_label("Create");
+    let move_button = Button::with_label("Move PID Here");
+    let limit_combo = ComboBoxText::new();
+    for limit in CgroupLimit::ALL {
+        limit_combo.append(Some(limit.as_str()), limit.file());
+    }
+    limit_combo.set_active_id(Some(CgroupLimit::Memory.as_str()));
+    let value_entry = Entry::builder().placeholder_text("50%, 512M, 100, 8:0 wbps=1048576 or max...").build();
+    let set_limit_button = Button::with_label("Set Limit");
+
+    let info_view = TextView::new();
+    info_view.set_editable(false);
+    info_view.set_cursor_visible(false);
+    info_view.set_monospace(true);
+    let scroll = ScrolledWindow::builder()
+        .vexpand(true)
+        .hexpand(true)
+        .child(&info_view)
+        .build();
+
+    // Every action first picks up the root, so a fake cgroupfs directory
+    // can be used without restarting.
+    let apply_root = Rc::new(clone!(@strong manager, @strong root_entry => move || {
+        let root = root_entry.text();
+        if !root.trim().is_empty() {
+            manager.borrow_mut().set_cgroup_root(root.trim());
+        }
+    }));
+
+    // Shows the cgroup of the PID if one is given, otherwise the path.
+    show_button.connect_clicked(clone!(@strong manager, @strong path_entry, @strong pid_entry, @strong info_view, @strong apply_root => move |_| {
+        apply_root();
+        let path = match pid_entry.text().trim().parse::<usize>() {
+            Ok(pid) => {
+                let result = manager.borrow_mut().cgroup_of(pid);
+                match result {
+                    Ok(path) => path,
+                    Err(e) => {
+                        info_view.buffer().set_text(&format!("⚠️ Failed to get cgroup of {}: {}", pid, e));
+                        return;
+                    }
+                }
+            }
+            Err(_) => path_entry.text().to_string(),
+        };
+        let result = manager.borrow_mut().cgroup_info(&path);
+        let text = match result {
+            Ok(info) => info.to_string(),
+            Err(e) => format!("⚠️ Failed to read cgroup {}: {}", path, e),
+        };
+        info_view.buffer().set_text(&text);
+    }));
+
+    create_button.connect_clicked(clone!(@strong manager, @strong path_entry, @strong info_view, @strong apply_root => move |_| {
+        apply_root();
+        let path = path_entry.text();
+        let result = manager.borrow_mut().create_cgroup(&path);
+        let msg = match result {
+            Ok(()) => format!("✅ Created cgroup {}", path),
+            Err(e) => format!("⚠️ Failed to create cgroup {}: {}", path, e),
+        };
+        info_view.buffer().set_text(&msg);
+    }));
+
+    move_button.connect_clicked(clone!(@strong manager, @strong path_entry, @strong pid_entry, @strong info_view, @strong apply_root => move |_| {
+        apply_root();
+        let path = path_entry.text();
+        if let Ok(pid) = pid_entry.text().trim().parse::<usize>() {
+            let result = manager.borrow_mut().move_to_cgroup(pid, &path);
+            let msg = match result {
+                Ok(()) => format!("✅ Moved {} to cgroup {}", pid, path),
+                Err(e) => format!("⚠️ Failed to move {} to cgroup {}: {}", pid, path, e),
+            };
+            info_view.buffer().set_text(&msg);
+        }
+    }));
+
+    set_limit_button.connect_clicked(clone!(@strong manager, @strong path_entry, @strong limit_combo, @strong value_entry, @strong info_view, @strong apply_root => move |_| {
+        apply_root();
+        let path = path_entry.text();
+        let value = value_entry.text();
+        if let Some(limit) = limit_combo.active_id().and_then(|id| id.parse::<CgroupLimit>().ok()) {
+            let result = manager.borrow_mut().set_cgroup_limit(&path, limit, &value);
+            let msg = match result {
+                Ok(()) => format!("✅ Set {} of {} to {}", limit.file(), path, value),
+                Err(e) => format!("⚠️ Failed to set {} of {}: {}", limit.file(), path, e),
+            };
+            info_view.buffer().set_text(&msg);
+        }
+    }));
+
+    let root_box = GtkBox::new(Orientation::Horizontal, 5);
+    root_box.append(&Label::new(Some("Root:")));
+    root_box.append(&root_entry);
+
+    let target_box = GtkBox::new(Orientation::Horizontal, 5);
+    target_box.append(&path_entry);
+    target_box.append(&pid_entry);
+    target_box.append(&show_button);
+    target_box.append(&create_button);
+    target_box.append(&move_button);
+
+    let limit_box = GtkBox::new(Orientation::Horizontal, 5);
+    limit_box.append(&limit_combo);
+    limit_box.append(&value_entry);
+    limit_box.append(&set_limit_button);
+
+    vbox.append(&root_box);
+    vbox.append(&target_box);
+    vbox.append(&limit_box);
+    vbox.append(&scroll);
+    vbox
+}
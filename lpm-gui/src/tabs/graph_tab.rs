@@ -1,17 +1,21 @@
 // src/tabs/graph_tab.rs
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, DrawingArea as GtkDrawingArea, Orientation};
+use gtk::{Box as GtkBox, DrawingArea as GtkDrawingArea, Entry, Label, Orientation};
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::time::Duration;
 use sysinfo::{CpuExt, System, SystemExt};
 use gtk::glib;
+use lpm_core::{ProcessManager, Sample};
 use plotters::prelude::*;
 use plotters_cairo::CairoBackend;
 use plotters::coord::Shift;
 use cairo::Context as CairoContext; // ✅ Correct cairo context
 
-pub fn build_graph_tab() -> GtkBox {
+/// How far back the per-process charts look.
+const PROCESS_WINDOW: Duration = Duration::from_secs(60);
+
+pub fn build_graph_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
     let vbox = GtkBox::new(Orientation::Vertical, 10);
 
     let line_row = GtkBox::new(Orientation::Horizontal, 10);
@@ -39,6 +43,23 @@ pub fn build_graph_tab() -> GtkBox {
     pie_row.append(&pie_chart);
     pie_row.append(&swap_pie_chart);
 
+    let pid_row = GtkBox::new(Orientation::Horizontal, 10);
+    let pid_entry = Entry::new();
+    pid_entry.set_placeholder_text(Some("PID to chart"));
+    let pid_status = Label::new(Some("Enter a PID to chart its CPU and memory over the last minute"));
+    pid_row.append(&pid_entry);
+    pid_row.append(&pid_status);
+
+    let process_row = GtkBox::new(Orientation::Horizontal, 10);
+    let process_cpu_chart = GtkDrawingArea::new();
+    process_cpu_chart.set_content_width(600);
+    process_cpu_chart.set_content_height(300);
+    let process_mem_chart = GtkDrawingArea::new();
+    process_mem_chart.set_content_width(600);
+    process_mem_chart.set_content_height(300);
+    process_row.append(&process_cpu_chart);
+    process_row.append(&process_mem_chart);
+
     let cpu_data = Rc::new(RefCell::new(vec![0f32; 60]));
     let mem_data = Rc::new(RefCell::new(vec![0f32; 60]));
     let system = Rc::new(RefCell::new(System::new_all()));
@@ -80,11 +101,31 @@ pub fn build_graph_tab() -> GtkBox {
         draw_pie_chart(&pie_chart_clone, used_mem, total_mem, String::from("Memory"));
         draw_pie_chart(&swap_pie_clone, used_swap, total_swap, String::from("Swap"));
 
+        let pid = pid_entry.text().trim().parse::<usize>().ok();
+        // Skip this tick rather than panic if another tab holds the manager.
+        if let (Some(pid), Ok(mut mgr)) = (pid, manager.try_borrow_mut()) {
+            mgr.sample_metrics();
+            let samples = mgr.history_for(pid, PROCESS_WINDOW);
+            if samples.is_empty() {
+                pid_status.set_text(&format!("Process {} is not running", pid));
+            } else {
+                pid_status.set_text(&format!("{} samples of process {}", samples.len(), pid));
+            }
+            let cpu = trend_points(&samples, |s| s.cpu_usage);
+            let rss = trend_points(&samples, |s| s.memory as f32 / (1024.0 * 1024.0));
+            let cpu_max = cpu.iter().map(|p| p.1).fold(100.0, f32::max);
+            let rss_max = rss.iter().map(|p| p.1).fold(1.0, f32::max) * 1.2;
+            draw_trend_chart(&process_cpu_chart, cpu, &format!("PID {} CPU (%)", pid), cpu_max);
+            draw_trend_chart(&process_mem_chart, rss, &format!("PID {} RSS (MiB)", pid), rss_max);
+        }
+
         glib::Continue(true)
     });
 
     vbox.append(&line_row);
     vbox.append(&pie_row);
+    vbox.append(&pid_row);
+    vbox.append(&process_row);
 
     vbox
 }
@@ -120,6 +161,46 @@ fn draw_line_chart(area: &GtkDrawingArea, data: Vec<f32>, label: &str) {
     });
 }
 
+/// Maps samples to (seconds before the newest sample, value) points.
+fn trend_points(samples: &[Sample], value: impl Fn(&Sample) -> f32) -> Vec<(f32, f32)> {
+    let Some(newest) = samples.last().map(|s| s.timestamp) else {
+        return Vec::new();
+    };
+    samples
+        .iter()
+        .map(|s| ((s.timestamp - newest).num_milliseconds() as f32 / 1000.0, value(s)))
+        .collect()
+}
+
+fn draw_trend_chart(area: &GtkDrawingArea, data: Vec<(f32, f32)>, label: &str, y_max: f32) {
+    let label = label.to_string();
+    let window = PROCESS_WINDOW.as_secs_f32();
+    area.set_draw_func(move |_, cr, width, height| {
+        let cairo_ctx = CairoContext::new(cr.target()).unwrap();
+        let backend = CairoBackend::new(&cairo_ctx, (width as u32, height as u32)).unwrap();
+        let root = backend.into_drawing_area();
+        root.fill(&WHITE).unwrap();
+
+        let _ = ChartBuilder::on(&root)
+            .margin(10)
+            .caption(&label, ("sans-serif", 14))
+            .x_label_area_size(30)
+            .y_label_area_size(40)
+            .build_cartesian_2d(-window..0f32, 0f32..y_max)
+            .and_then(|mut chart| {
+                chart.configure_mesh()
+                    .x_desc("Seconds ago")
+                    .y_desc(label.clone())
+                    .disable_mesh()
+                    .draw()?;
+                chart.draw_series(LineSeries::new(data.iter().copied(), &BLUE))?;
+                Ok(())
+            });
+
+        root.present().unwrap();
+    });
+}
+
 fn draw_pie_chart(area: &GtkDrawingArea, used: f32, total: f32, label: String) {
     area.set_draw_func(move |_, cr, width, height| {
         let cairo_ctx = CairoContext::new(cr.target()).unwrap();
@@ -1,8 +1,8 @@
 use gtk::prelude::*;
-use gtk::{Box as GtkBox, Orientation, ScrolledWindow, TextView, Button};
+use gtk::{Box as GtkBox, Orientation, ScrolledWindow, TextView, Button, ComboBoxText, Entry};
 use std::cell::RefCell;
 use std::rc::Rc;
-use lpm_core::ProcessManager;
+use lpm_core::{parse_time, ActionKind, HistoryQuery, ProcessManager};
 use gtk::glib::clone;
 
 pub fn build_history_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
@@ -18,19 +18,45 @@ pub fn build_history_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
         .child(&history_view)
         .build();
 
+    // Filters
+    let since_entry = Entry::builder().placeholder_text("Since (YYYY-MM-DD [HH:MM])...").build();
+    let until_entry = Entry::builder().placeholder_text("Until (YYYY-MM-DD [HH:MM])...").build();
+    let pid_entry = Entry::builder().placeholder_text("PID...").build();
+    let action_combo = ComboBoxText::new();
+    action_combo.append(None, "all actions");
+    for action in ActionKind::ALL {
+        action_combo.append(Some(action.as_str()), action.as_str());
+    }
+    action_combo.set_active(Some(0));
+
+    let filter_box = GtkBox::new(Orientation::Horizontal, 5);
+    filter_box.append(&since_entry);
+    filter_box.append(&until_entry);
+    filter_box.append(&action_combo);
+    filter_box.append(&pid_entry);
+
+    let refresh = Rc::new(clone!(@strong manager, @strong history_view, @strong since_entry,
+        @strong until_entry, @strong pid_entry, @strong action_combo => move || {
+        let query = HistoryQuery {
+            since: parse_time(&since_entry.text()),
+            until: parse_time(&until_entry.text()),
+            action: action_combo.active_id().and_then(|id| id.parse().ok()),
+            pid: pid_entry.text().parse().ok(),
+        };
+        let text = manager.borrow().show_history(&query);
+        history_view.buffer().set_text(&text);
+    }));
+
     // Initial load
-    let history_text = manager.borrow().show_history();
-    history_view.buffer().set_text(&history_text);
+    refresh();
 
     // Refresh button
     let refresh_button = Button::with_label("Refresh History");
-    refresh_button.connect_clicked(clone!(@strong manager, @strong history_view => move |_| {
-        let text = manager.borrow().show_history();
-        history_view.buffer().set_text(&text);
-    }));
+    refresh_button.connect_clicked(clone!(@strong refresh => move |_| refresh()));
+    action_combo.connect_changed(clone!(@strong refresh => move |_| refresh()));
 
+    vbox.append(&filter_box);
     vbox.append(&refresh_button);
     vbox.append(&scroll);
     vbox
 }
-
@@ -1,38 +1,61 @@
 // src/tabs/process_tab.rs
 use gtk::prelude::*;
 use gtk::{
-    Box as GtkBox, Button, CellRendererText, ComboBoxText, Entry, Label, ListStore, Orientation,
-    ScrolledWindow, TextView, TreeView, TreeViewColumn,
+    Box as GtkBox, Button, CellRendererText, CheckButton, ComboBoxText, Entry, Grid, Label, Orientation,
+    ScrolledWindow, TextView, TreeStore, TreeView, TreeViewColumn, Window,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
-use sysinfo::{PidExt, ProcessExt};
-use lpm_core::ProcessManager;
+use lpm_core::{
+    format_cpu_list, online_cpus, resolve_group, HistoryQuery, IoClass, ProcessInfo, ProcessManager, ProcessState,
+    Query, RestartOptions, SchedPolicy, Signal, TermStage, DEFAULT_GRACE, OOM_SCORE_ADJ_MAX, OOM_SCORE_ADJ_MIN,
+};
 use gtk::glib::clone;
 
 pub fn build_process_tab(manager: Rc<RefCell<ProcessManager>>) -> (GtkBox, TextView) {
     let vbox = GtkBox::new(Orientation::Vertical, 5);
 
     let name_filter = Entry::builder().placeholder_text("Filter by process name...").build();
-    let user_filter = Entry::builder().placeholder_text("Filter by user...").build();
+    let user_filter = Entry::builder().placeholder_text("Filter by user name or uid...").build();
+    let group_filter = Entry::builder().placeholder_text("Filter by group name or gid...").build();
+    let query_entry = Entry::builder()
+        .placeholder_text("Query, e.g. cpu > 50 and user == \"postgres\" and cmd ~ /--worker/")
+        .build();
     let pid_entry = Entry::builder().placeholder_text("Enter PID...").build();
     let priority_entry = Entry::builder().placeholder_text("Set priority (nice value)...").build();
     let sort_combo = ComboBoxText::new();
     sort_combo.append_text("cpu");
     sort_combo.append_text("memory");
+    sort_combo.append_text("oom");
     sort_combo.append_text("pid");
     sort_combo.append_text("name");
 
-    let store = ListStore::new(&[
+    // Processes are top-level rows; their threads are filled in as child rows
+    // when a row is expanded. Scheduling, I/O priority and OOM columns are
+    // only set for processes, the last three columns only for threads.
+    let store = TreeStore::new(&[
         u32::static_type(),
         String::static_type(),
         f32::static_type(),
-        u64::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
+        String::static_type(),
         String::static_type(),
     ]);
 
     let tree_view = TreeView::with_model(&store);
-    for (i, title) in ["PID", "Name", "CPU %", "Memory (KB)", "User"].iter().enumerate() {
+    let titles = [
+        "PID", "Name", "CPU %", "Memory (KB)", "User", "State", "Policy", "RT Prio", "I/O Prio", "OOM Score", "OOM Adj",
+        "Last CPU", "Nice", "Affinity",
+    ];
+    for (i, title) in titles.iter().enumerate() {
         let column = TreeViewColumn::new();
         column.set_title(title);
         let cell = CellRendererText::new();
@@ -48,10 +71,38 @@ pub fn build_process_tab(manager: Rc<RefCell<ProcessManager>>) -> (GtkBox, TextV
         .build();
 
     let kill_button = Button::with_label("Kill Process");
+    let force_check = CheckButton::with_label("Force (SIGKILL now)");
     let restart_button = Button::with_label("Restart Process");
+    let suspend_button = Button::with_label("Suspend");
+    let resume_button = Button::with_label("Resume");
+    let subtree_check = CheckButton::with_label("Include subtree");
     let alert_button = Button::with_label("Check Alerts");
     let history_button = Button::with_label("Show History");
+    let details_button = Button::with_label("Details");
+    let affinity_button = Button::with_label("CPU Affinity");
+    let protect_button = Button::with_label("Protect from OOM");
+    let sacrifice_button = Button::with_label("Sacrifice First");
     let set_priority_button = Button::with_label("Set Priority");
+    let policy_combo = ComboBoxText::new();
+    for policy in SchedPolicy::ALL.iter().filter(|p| **p != SchedPolicy::Deadline) {
+        policy_combo.append(Some(policy.as_str()), policy.as_str());
+    }
+    policy_combo.set_active_id(Some(SchedPolicy::Other.as_str()));
+    let rt_priority_entry = Entry::builder().placeholder_text("RT priority (1-99)...").build();
+    let set_sched_button = Button::with_label("Set Scheduling");
+    let io_class_combo = ComboBoxText::new();
+    for class in IoClass::ALL {
+        io_class_combo.append(Some(class.as_str()), class.as_str());
+    }
+    io_class_combo.set_active_id(Some(IoClass::BestEffort.as_str()));
+    let io_level_entry = Entry::builder().placeholder_text("I/O level (0-7)...").build();
+    let set_io_button = Button::with_label("Set I/O Priority");
+    let signal_combo = ComboBoxText::new();
+    for signal in Signal::all() {
+        signal_combo.append(Some(&signal.name()), &signal.to_string());
+    }
+    signal_combo.set_active_id(Some("TERM"));
+    let send_signal_button = Button::with_label("Send Signal");
     let count_label = Label::new(Some("Selected: 0 processes"));
 
     let history_view = TextView::new();
@@ -70,56 +121,136 @@ pub fn build_process_tab(manager: Rc<RefCell<ProcessManager>>) -> (GtkBox, TextV
         let store = store.clone();
         let name_filter = name_filter.clone();
         let user_filter = user_filter.clone();
+        let group_filter = group_filter.clone();
+        let query_entry = query_entry.clone();
         let sort_combo = sort_combo.clone();
         let count_label = count_label.clone();
 
         move || {
-            let mut mgr = manager.borrow_mut();
-            let mut processes = if !name_filter.text().is_empty() {
-                mgr.list_processes_by_name(&name_filter.text())
-            } else if !user_filter.text().is_empty() {
-                mgr.list_processes_by_user(&user_filter.text())
+            let query_text = query_entry.text();
+            let query = if query_text.trim().is_empty() {
+                None
+            } else {
+                match Query::parse(&query_text) {
+                    Ok(query) => Some(query),
+                    Err(e) => {
+                        count_label.set_text(&format!("⚠️ Invalid query: {}", e));
+                        return;
+                    }
+                }
+            };
+            let name = name_filter.text().to_lowercase();
+            let user = user_filter.text();
+            let group_text = group_filter.text();
+            let group = if group_text.is_empty() {
+                None
             } else {
-                mgr.list_processes()
+                match resolve_group(&group_text) {
+                    Some(gid) => Some(gid),
+                    None => {
+                        count_label.set_text(&format!("⚠️ Unknown group: {}", group_text));
+                        return;
+                    }
+                }
             };
 
+            let mut mgr = manager.borrow_mut();
+            let mut processes: Vec<_> = mgr
+                .list_processes()
+                .into_iter()
+                .filter(|p| name.is_empty() || p.name.to_lowercase().contains(&name))
+                .filter(|p| user.is_empty() || p.matches_user(&user))
+                .filter(|p| group.is_none_or(|gid| p.in_group(gid)))
+                .filter(|p| query.as_ref().is_none_or(|q| q.matches(p)))
+                .collect();
+
             let sort_key = sort_combo.active_text().unwrap_or_default();
             match sort_key.as_str() {
-                "cpu" => processes.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap_or(std::cmp::Ordering::Equal)),
-                "memory" => processes.sort_by(|a, b| b.memory().cmp(&a.memory())),
-                "pid" => processes.sort_by(|a, b| a.pid().cmp(&b.pid())),
-                "name" => processes.sort_by(|a, b| a.name().cmp(&b.name())),
+                "cpu" => processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(std::cmp::Ordering::Equal)),
+                "memory" => processes.sort_by_key(|p| std::cmp::Reverse(p.memory)),
+                "oom" => processes.sort_by_key(|p| std::cmp::Reverse(p.oom_score)),
+                "pid" => processes.sort_by_key(|p| p.pid),
+                "name" => processes.sort_by(|a, b| a.name.cmp(&b.name)),
                 _ => {}
             }
 
             store.clear();
             for p in &processes {
-                let iter = store.append();
+                let iter = store.append(None);
                 store.set(&iter, &[
-                    (0, &p.pid().as_u32()),
-                    (1, &p.name().to_string()),
-                    (2, &p.cpu_usage()),
-                    (3, &p.memory()),
-                    (4, &format!("{:?}", p.user_id())),
+                    (0, &p.pid),
+                    (1, &p.name),
+                    (2, &p.cpu_usage),
+                    (3, &(p.memory / 1024).to_string()),
+                    (4, &p.user_label()),
+                    (5, &state_label(p.state)),
+                    (6, &p.scheduling.policy.to_string()),
+                    (7, &p.scheduling.priority.to_string()),
+                    (8, &p.io_priority.to_string()),
+                    (9, &p.oom_score.to_string()),
+                    (10, &p.oom_score_adj.to_string()),
                 ]);
+                // An empty child makes the row expandable; expanding it
+                // replaces the placeholder with the threads.
+                if p.threads > 1 {
+                    store.append(Some(&iter));
+                }
             }
 
             count_label.set_text(&format!("Shown: {} processes", processes.len()));
         }
     };
 
+    tree_view.connect_row_expanded(clone!(@strong manager, @strong store => move |_, iter, _| {
+        let pid = store.get::<u32>(iter, 0);
+        let result = manager.borrow_mut().threads(pid as usize);
+        let Ok(threads) = result else {
+            return;
+        };
+        let stale = store.iter_n_children(Some(iter));
+        for t in &threads {
+            let child = store.append(Some(iter));
+            store.set(&child, &[
+                (0, &t.tid),
+                (1, &t.name),
+                (2, &t.cpu_usage),
+                (5, &state_label(t.state)),
+                (11, &t.last_cpu.to_string()),
+                (12, &t.nice.to_string()),
+                (13, &format_cpu_list(&t.affinity)),
+            ]);
+        }
+        // Removed only after appending, so the row stays expanded.
+        for _ in 0..stale {
+            if let Some(child) = store.iter_children(Some(iter)) {
+                store.remove(&child);
+            }
+        }
+    }));
+
     let update_display_rc = Rc::new(update_display);
     name_filter.connect_changed(clone!(@strong update_display_rc => move |_| update_display_rc()));
     user_filter.connect_changed(clone!(@strong update_display_rc => move |_| update_display_rc()));
+    group_filter.connect_changed(clone!(@strong update_display_rc => move |_| update_display_rc()));
+    // Re-running on every keystroke would flag half-typed queries as errors.
+    query_entry.connect_activate(clone!(@strong update_display_rc => move |_| update_display_rc()));
     sort_combo.connect_changed(clone!(@strong update_display_rc => move |_| update_display_rc()));
 
-    kill_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong update_display_rc, @strong history_view => move |_| {
+    kill_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong force_check, @strong update_display_rc, @strong history_view => move |_| {
         if let Ok(pid) = pid_entry.text().parse::<usize>() {
-            let mut mgr = manager.borrow_mut();
-            let msg = if mgr.kill_process(pid) {
-                format!("✅ Killed process {}", pid)
+            let msg = if force_check.is_active() {
+                let result = manager.borrow_mut().kill_process(pid);
+                match result {
+                    Ok(()) => format!("✅ Killed process {}", pid),
+                    Err(e) => format!("⚠️ Failed to kill process {}: {}", pid, e),
+                }
             } else {
-                format!("⚠️ Failed to kill process {}", pid)
+                let result = manager.borrow_mut().terminate(pid, DEFAULT_GRACE);
+                match result {
+                    Ok(TermStage::Terminated) => format!("✅ Terminated process {} with SIGTERM", pid),
+                    Ok(TermStage::Killed) => format!("✅ Killed process {} with SIGKILL after the grace period", pid),
+                    Err(e) => format!("⚠️ Failed to terminate process {}: {}", pid, e),
+                }
             };
             update_display_rc();
             history_view.buffer().set_text(&msg);
@@ -128,49 +259,199 @@ pub fn build_process_tab(manager: Rc<RefCell<ProcessManager>>) -> (GtkBox, TextV
 
     restart_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong update_display_rc, @strong history_view => move |_| {
         if let Ok(pid) = pid_entry.text().parse::<usize>() {
-            let mut mgr = manager.borrow_mut();
-            let msg = if mgr.restart_process(pid) {
-                format!("✅ Restarted process {}", pid)
-            } else {
-                format!("⚠️ Failed to restart process {}", pid)
+            // Detached, so the new process does not die with the GUI.
+            let options = RestartOptions { detach: true, ..RestartOptions::default() };
+            let result = manager.borrow_mut().restart_process(pid, &options);
+            let msg = match result {
+                Ok(new_pid) => format!("✅ Restarted process {} as {}", pid, new_pid),
+                Err(e) => format!("⚠️ Failed to restart process {}: {}", pid, e),
             };
             update_display_rc();
             history_view.buffer().set_text(&msg);
         }
     }));
 
+    suspend_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong subtree_check, @strong update_display_rc, @strong history_view => move |_| {
+        if let Ok(pid) = pid_entry.text().parse::<usize>() {
+            let msg = suspend_or_resume(&mut manager.borrow_mut(), pid, subtree_check.is_active(), true);
+            update_display_rc();
+            history_view.buffer().set_text(&msg);
+        }
+    }));
+
+    resume_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong subtree_check, @strong update_display_rc, @strong history_view => move |_| {
+        if let Ok(pid) = pid_entry.text().parse::<usize>() {
+            let msg = suspend_or_resume(&mut manager.borrow_mut(), pid, subtree_check.is_active(), false);
+            update_display_rc();
+            history_view.buffer().set_text(&msg);
+        }
+    }));
+
     set_priority_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong priority_entry, @strong history_view => move |_| {
         if let (Ok(pid), Ok(priority)) = (pid_entry.text().parse::<usize>(), priority_entry.text().parse::<i32>()) {
             let mut mgr = manager.borrow_mut();
-            let msg = if mgr.change_priority(pid, priority) {
-                format!("✅ Changed priority of {} to {}", pid, priority)
-            } else {
-                format!("⚠️ Failed to change priority for {}", pid)
+            let msg = match mgr.change_priority(pid, priority) {
+                Ok(()) => format!("✅ Changed priority of {} to {}", pid, priority),
+                Err(e) => format!("⚠️ Failed to change priority for {}: {}", pid, e),
             };
             history_view.buffer().set_text(&msg);
         }
     }));
 
+    set_sched_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong policy_combo, @strong rt_priority_entry, @strong update_display_rc, @strong history_view => move |_| {
+        let policy = policy_combo.active_id().and_then(|id| id.parse::<SchedPolicy>().ok());
+        if let (Ok(pid), Some(policy)) = (pid_entry.text().parse::<usize>(), policy) {
+            let priority = match rt_priority_entry.text().trim() {
+                "" if policy.is_realtime() => 1,
+                "" => 0,
+                text => match text.parse::<u32>() {
+                    Ok(priority) => priority,
+                    Err(_) => {
+                        history_view.buffer().set_text(&format!("⚠️ Invalid RT priority: {}", text));
+                        return;
+                    }
+                },
+            };
+            let result = manager.borrow_mut().set_scheduling(pid, policy, priority);
+            let msg = match result {
+                Ok(()) => format!("✅ Set scheduling of {} to {} with priority {}", pid, policy, priority),
+                Err(e) => format!("⚠️ Failed to set scheduling for {}: {}", pid, e),
+            };
+            update_display_rc();
+            history_view.buffer().set_text(&msg);
+        }
+    }));
+
+    set_io_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong io_class_combo, @strong io_level_entry, @strong update_display_rc, @strong history_view => move |_| {
+        let class = io_class_combo.active_id().and_then(|id| id.parse::<IoClass>().ok());
+        if let (Ok(pid), Some(class)) = (pid_entry.text().parse::<usize>(), class) {
+            let level = match io_level_entry.text().trim() {
+                "" if class.has_level() => 4,
+                "" => 0,
+                text => match text.parse::<u8>() {
+                    Ok(level) => level,
+                    Err(_) => {
+                        history_view.buffer().set_text(&format!("⚠️ Invalid I/O level: {}", text));
+                        return;
+                    }
+                },
+            };
+            let result = manager.borrow_mut().set_io_priority(pid, class, level);
+            let msg = match result {
+                Ok(()) => format!("✅ Set I/O priority of {} to {} with level {}", pid, class, level),
+                Err(e) => format!("⚠️ Failed to set I/O priority for {}: {}", pid, e),
+            };
+            update_display_rc();
+            history_view.buffer().set_text(&msg);
+        }
+    }));
+
+    send_signal_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong signal_combo, @strong update_display_rc, @strong history_view => move |_| {
+        let signal = signal_combo.active_id().and_then(|id| id.parse::<Signal>().ok());
+        if let (Ok(pid), Some(signal)) = (pid_entry.text().parse::<usize>(), signal) {
+            let result = manager.borrow_mut().send_signal(pid, signal);
+            let msg = match result {
+                Ok(()) => format!("✅ Sent {} to process {}", signal, pid),
+                Err(e) => format!("⚠️ Failed to send {} to process {}: {}", signal, pid, e),
+            };
+            update_display_rc();
+            history_view.buffer().set_text(&msg);
+        }
+    }));
+
     history_button.connect_clicked(clone!(@strong manager, @strong history_view => move |_| {
         let mgr = manager.borrow();
-        let text = mgr.show_history();
+        let text = mgr.show_history(&HistoryQuery::default());
         history_view.buffer().set_text(&text);
     }));
 
+    details_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong history_view => move |button| {
+        if let Ok(pid) = pid_entry.text().parse::<usize>() {
+            let result = manager.borrow_mut().inspect(pid);
+            match result {
+                Ok(info) => {
+                    let text = details_text(&mut manager.borrow_mut(), &info);
+                    let parent = button.root().and_then(|r| r.downcast::<Window>().ok());
+                    show_details(parent.as_ref(), &info, &text);
+                }
+                Err(e) => history_view.buffer().set_text(&format!("⚠️ Failed to inspect process {}: {}", pid, e)),
+            }
+        }
+    }));
+
+    affinity_button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong history_view => move |button| {
+        if let Ok(pid) = pid_entry.text().parse::<usize>() {
+            let result = manager.borrow_mut().get_affinity(pid);
+            match result {
+                Ok(cpus) => {
+                    let parent = button.root().and_then(|r| r.downcast::<Window>().ok());
+                    show_affinity(parent.as_ref(), Rc::clone(&manager), pid, &cpus, history_view.clone());
+                }
+                Err(e) => history_view.buffer().set_text(&format!("⚠️ Failed to get affinity of process {}: {}", pid, e)),
+            }
+        }
+    }));
+
+    // Both ends of oom_score_adj: never chosen by the OOM killer, or chosen
+    // before anything else.
+    for (button, adj) in [(&protect_button, OOM_SCORE_ADJ_MIN), (&sacrifice_button, OOM_SCORE_ADJ_MAX)] {
+        button.connect_clicked(clone!(@strong manager, @strong pid_entry, @strong update_display_rc, @strong history_view => move |_| {
+            if let Ok(pid) = pid_entry.text().parse::<usize>() {
+                let result = manager.borrow_mut().set_oom_score_adj(pid, adj);
+                let msg = match result {
+                    Ok(()) => format!("✅ Set OOM score adjustment of {} to {}", pid, adj),
+                    Err(e) => format!("⚠️ Failed to set OOM score adjustment for {}: {}", pid, e),
+                };
+                update_display_rc();
+                history_view.buffer().set_text(&msg);
+            }
+        }));
+    }
+
     let button_box = GtkBox::new(Orientation::Horizontal, 5);
     button_box.append(&kill_button);
+    button_box.append(&force_check);
     button_box.append(&restart_button);
+    button_box.append(&suspend_button);
+    button_box.append(&resume_button);
+    button_box.append(&subtree_check);
     button_box.append(&alert_button);
     button_box.append(&history_button);
+    button_box.append(&details_button);
+    button_box.append(&affinity_button);
+
+    let sched_box = GtkBox::new(Orientation::Horizontal, 5);
+    sched_box.append(&policy_combo);
+    sched_box.append(&rt_priority_entry);
+    sched_box.append(&set_sched_button);
+
+    let io_box = GtkBox::new(Orientation::Horizontal, 5);
+    io_box.append(&io_class_combo);
+    io_box.append(&io_level_entry);
+    io_box.append(&set_io_button);
+
+    let oom_box = GtkBox::new(Orientation::Horizontal, 5);
+    oom_box.append(&protect_button);
+    oom_box.append(&sacrifice_button);
+
+    let signal_box = GtkBox::new(Orientation::Horizontal, 5);
+    signal_box.append(&signal_combo);
+    signal_box.append(&send_signal_button);
 
     vbox.append(&name_filter);
     vbox.append(&user_filter);
+    vbox.append(&group_filter);
+    vbox.append(&query_entry);
     vbox.append(&sort_combo);
     vbox.append(&scrolled_window);
     vbox.append(&button_box);
     vbox.append(&pid_entry);
     vbox.append(&priority_entry);
     vbox.append(&set_priority_button);
+    vbox.append(&sched_box);
+    vbox.append(&io_box);
+    vbox.append(&oom_box);
+    vbox.append(&signal_box);
     vbox.append(&count_label);
     vbox.append(&history_scroll);
 
@@ -179,3 +460,171 @@ pub fn build_process_tab(manager: Rc<RefCell<ProcessManager>>) -> (GtkBox, TextV
     (vbox, history_view)
 }
 
+/// Everything `inspect` found plus the environment, memory breakdown, open
+/// files, resource limits and sockets. Sections lpm may not read show
+/// "(not readable)".
+fn details_text(mgr: &mut ProcessManager, info: &ProcessInfo) -> String {
+    let pid = info.pid as usize;
+    let mut text = info.to_string();
+    text.push_str("\n\nEnvironment:\n");
+    match &info.environment {
+        Some(vars) => {
+            for (key, value) in vars {
+                text.push_str(&format!("  {}={}\n", key, value));
+            }
+        }
+        None => text.push_str("  (not readable)\n"),
+    }
+    text.push_str("\nMemory:\n");
+    match mgr.memory_usage(pid) {
+        Ok(usage) => {
+            for line in usage.to_string().lines() {
+                text.push_str(&format!("  {}\n", line));
+            }
+            if let Ok(mappings) = mgr.top_mappings(pid, 10) {
+                text.push_str(&format!("\n  {:>10}  {:>10}  {:>10}  Top mappings\n", "PSS KB", "RSS KB", "USS KB"));
+                for m in mappings {
+                    text.push_str(&format!(
+                        "  {:>10}  {:>10}  {:>10}  {}\n",
+                        m.usage.pss / 1024,
+                        m.usage.rss / 1024,
+                        m.usage.uss / 1024,
+                        m.path
+                    ));
+                }
+            }
+        }
+        Err(_) => text.push_str("  (not readable)\n"),
+    }
+    text.push_str("\nOpen files:\n");
+    match mgr.open_files(pid) {
+        Ok(files) => {
+            text.push_str(&format!("  {}\n", files));
+            for f in &files.files {
+                let size = f.size.map_or(String::new(), |s| format!(" ({} bytes)", s));
+                text.push_str(&format!("  {:>5}  {:<10}  {}{}\n", f.fd, f.kind.as_str(), f.target, size));
+            }
+        }
+        Err(_) => text.push_str("  (not readable)\n"),
+    }
+    text.push_str("\nLimits:\n");
+    match mgr.limits(pid) {
+        Ok(limits) => {
+            text.push_str(&format!("  {:<12}  {:>20}  {:>20}  {}\n", "Resource", "Soft", "Hard", "Units"));
+            for l in limits {
+                text.push_str(&format!(
+                    "  {:<12}  {:>20}  {:>20}  {}\n",
+                    l.resource,
+                    l.soft_label(),
+                    l.hard_label(),
+                    l.resource.units()
+                ));
+            }
+        }
+        Err(_) => text.push_str("  (not readable)\n"),
+    }
+    text.push_str("\nConnections:\n");
+    match mgr.connections(pid).as_deref() {
+        Ok([]) => text.push_str("  (none)\n"),
+        Ok(connections) => {
+            for c in connections {
+                text.push_str(&format!("  {}\n", c));
+            }
+        }
+        Err(_) => text.push_str("  (not readable)\n"),
+    }
+    text
+}
+
+/// Opens a window showing `text` about the process in `info`.
+fn show_details(parent: Option<&Window>, info: &ProcessInfo, text: &str) {
+    let view = TextView::new();
+    view.set_editable(false);
+    view.set_cursor_visible(false);
+    view.set_monospace(true);
+    view.buffer().set_text(text);
+    let scroll = ScrolledWindow::builder().child(&view).vexpand(true).build();
+
+    let window = Window::builder()
+        .title(format!("Process {} ({})", info.pid, info.name))
+        .default_width(700)
+        .default_height(500)
+        .child(&scroll)
+        .build();
+    window.set_transient_for(parent);
+    window.present();
+}
+
+/// Opens a window with one checkbox per online CPU, checked for the CPUs
+/// `pid` may currently run on; "Apply" sets the checked ones on all threads.
+fn show_affinity(
+    parent: Option<&Window>,
+    manager: Rc<RefCell<ProcessManager>>,
+    pid: usize,
+    current: &[usize],
+    history_view: TextView,
+) {
+    const COLUMNS: usize = 8;
+    let grid = Grid::builder().row_spacing(5).column_spacing(10).build();
+    let checks: Vec<(usize, CheckButton)> = online_cpus()
+        .into_iter()
+        .enumerate()
+        .map(|(i, cpu)| {
+            let check = CheckButton::with_label(&format!("CPU {}", cpu));
+            check.set_active(current.contains(&cpu));
+            grid.attach(&check, (i % COLUMNS) as i32, (i / COLUMNS) as i32, 1, 1);
+            (cpu, check)
+        })
+        .collect();
+
+    let apply_button = Button::with_label("Apply");
+    let vbox = GtkBox::new(Orientation::Vertical, 10);
+    vbox.append(&Label::new(Some(&format!("Currently: CPUs {}", format_cpu_list(current)))));
+    vbox.append(&grid);
+    vbox.append(&apply_button);
+
+    let window = Window::builder()
+        .title(format!("CPU affinity of process {}", pid))
+        .child(&vbox)
+        .build();
+    window.set_transient_for(parent);
+
+    apply_button.connect_clicked(clone!(@strong window => move |_| {
+        let cpus: Vec<usize> = checks.iter().filter(|(_, c)| c.is_active()).map(|(cpu, _)| *cpu).collect();
+        let result = manager.borrow_mut().set_affinity(pid, &cpus);
+        let msg = match result {
+            Ok(()) => format!("✅ Set affinity of process {} to CPUs {}", pid, format_cpu_list(&cpus)),
+            Err(e) => format!("⚠️ Failed to set affinity of process {}: {}", pid, e),
+        };
+        history_view.buffer().set_text(&msg);
+        window.close();
+    }));
+    window.present();
+}
+
+fn state_label(state: ProcessState) -> String {
+    match state {
+        ProcessState::Stopped | ProcessState::Tracing => format!("⏸ {}", state),
+        _ => state.to_string(),
+    }
+}
+
+fn suspend_or_resume(mgr: &mut ProcessManager, pid: usize, subtree: bool, suspend: bool) -> String {
+    let (verb, done) = if suspend { ("suspend", "Suspended") } else { ("resume", "Resumed") };
+    if subtree {
+        let result = if suspend { mgr.suspend_tree(pid) } else { mgr.resume_tree(pid) };
+        match result {
+            Ok(results) => {
+                let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+                format!("✅ {} {} of {} processes in the subtree of {}", done, results.len() - failed, results.len(), pid)
+            }
+            Err(e) => format!("⚠️ Failed to {} subtree of {}: {}", verb, pid, e),
+        }
+    } else {
+        let result = if suspend { mgr.suspend(pid) } else { mgr.resume(pid) };
+        match result {
+            Ok(()) => format!("✅ {} process {}", done, pid),
+            Err(e) => format!("⚠️ Failed to {} process {}: {}", verb, pid, e),
+        }
+    }
+}
@@ -2,13 +2,14 @@
 
 use gtk::prelude::*;
 use gtk::{
-    Box as GtkBox, CellRendererText, Orientation, ScrolledWindow,
+    Box as GtkBox, Button, CellRendererText, Label, Orientation, ScrolledWindow,
     TreeStore, TreeView, TreeViewColumn,
 };
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use lpm_core::ProcessManager;
+use lpm_core::{ProcessManager, Signal, TreeOrder};
+use gtk::glib::clone;
 
 pub fn build_process_tree_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
     let vbox = GtkBox::new(Orientation::Vertical, 5);
@@ -38,9 +39,51 @@ pub fn build_process_tree_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
         .child(&tree_view)
         .build();
 
+    fill_tree(&tree_store, &mut manager.borrow_mut());
+
+    let refresh_button = Button::with_label("Refresh");
+    let kill_subtree_button = Button::with_label("Kill subtree");
+    let status_label = Label::new(Some("Select a process to act on its subtree"));
+
+    refresh_button.connect_clicked(clone!(@strong manager, @strong tree_store => move |_| {
+        fill_tree(&tree_store, &mut manager.borrow_mut());
+    }));
+
+    kill_subtree_button.connect_clicked(clone!(@strong manager, @strong tree_store, @strong tree_view, @strong status_label => move |_| {
+        let Some((model, iter)) = tree_view.selection().selected() else {
+            status_label.set_text("⚠️ No process selected");
+            return;
+        };
+        let pid = model.get::<u32>(&iter, 0) as usize;
+
+        let mut mgr = manager.borrow_mut();
+        // Parents first, so a supervisor cannot respawn children we just killed.
+        let msg = match mgr.signal_tree(pid, Signal::KILL, TreeOrder::RootFirst) {
+            Ok(results) => {
+                let failed = results.iter().filter(|(_, r)| r.is_err()).count();
+                format!("✅ Killed {} of {} processes in the subtree of {}", results.len() - failed, results.len(), pid)
+            }
+            Err(e) => format!("⚠️ Failed to kill subtree of {}: {}", pid, e),
+        };
+        fill_tree(&tree_store, &mut mgr);
+        status_label.set_text(&msg);
+    }));
+
+    let button_box = GtkBox::new(Orientation::Horizontal, 5);
+    button_box.append(&refresh_button);
+    button_box.append(&kill_subtree_button);
+
+    vbox.append(&scrolled);
+    vbox.append(&button_box);
+    vbox.append(&status_label);
+    vbox
+}
+
+fn fill_tree(tree_store: &TreeStore, manager: &mut ProcessManager) {
+    tree_store.clear();
+
     // Get the tree structure data
-    let mut manager_ref = manager.borrow_mut();
-    let process_data = manager_ref.get_process_tree();
+    let process_data = manager.get_process_tree();
 
     let mut pid_to_iter = HashMap::new();
 
@@ -62,8 +105,4 @@ pub fn build_process_tree_tab(manager: Rc<RefCell<ProcessManager>>) -> GtkBox {
 
         pid_to_iter.insert(pid, iter);
     }
-
-    vbox.append(&scrolled);
-    vbox
 }
-